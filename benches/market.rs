@@ -0,0 +1,75 @@
+//! Change history totals for the day 22 market, hashed vs dense and serial vs parallel.
+//!
+//! `cargo bench --bench market`
+
+use std::collections::{HashMap, HashSet};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use advent_of_code_2024::days::monkey::{
+    analyze_nums, step_number, History, HistoryTotals, Market, SeenHistories,
+};
+
+const STEPS: u64 = 2000;
+
+fn market(buyers: usize) -> Market {
+    let mut rng = StdRng::seed_from_u64(22);
+    Market {
+        buyers: (0..buyers).map(|_| rng.gen_range(1..1 << 24)).collect(),
+    }
+}
+
+/// How the totals used to be kept, as a baseline
+fn hashed_totals(market: &Market) -> HashMap<History, u64> {
+    let mut totals = HashMap::new();
+    for &seed in &market.buyers {
+        let mut secret = seed;
+        let mut changes = Vec::new();
+        let mut seen = HashSet::new();
+        for _ in 0..STEPS {
+            let next = step_number(secret);
+            changes.push((next % 10) as i8 - (secret % 10) as i8);
+            secret = next;
+
+            if let Some(&[a, b, c, d]) = changes.last_chunk::<4>() {
+                if seen.insert((a, b, c, d)) {
+                    *totals.entry((a, b, c, d)).or_default() += secret % 10;
+                }
+            }
+        }
+    }
+
+    totals
+}
+
+fn dense_totals(market: &Market) -> HistoryTotals {
+    let mut totals = HistoryTotals::new();
+    let mut seen = SeenHistories::new();
+    for &seed in &market.buyers {
+        analyze_nums(seed, STEPS, &mut totals, &mut seen);
+    }
+
+    totals
+}
+
+fn history_totals(c: &mut Criterion) {
+    let mut group = c.benchmark_group("history totals");
+    group.sample_size(10);
+    for buyers in [1000, 4000] {
+        let market = market(buyers);
+        group.bench_function(BenchmarkId::new("hashed", buyers), |b| {
+            b.iter(|| hashed_totals(black_box(&market)))
+        });
+        group.bench_function(BenchmarkId::new("dense", buyers), |b| {
+            b.iter(|| dense_totals(black_box(&market)))
+        });
+        group.bench_function(BenchmarkId::new("dense parallel", buyers), |b| {
+            b.iter(|| black_box(&market).history_totals(STEPS))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, history_totals);
+criterion_main!(benches);
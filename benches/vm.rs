@@ -0,0 +1,57 @@
+//! Interpreted vs compiled runs of the day 17 program.
+//!
+//! `cargo bench --bench vm`
+
+use std::fs;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use advent_of_code_2024::days::chronospatial_comp::{
+    compiled::CompiledProgram, run, Computer, State,
+};
+
+fn computer() -> Computer {
+    fs::read_to_string("inputs/day17/input.txt")
+        .expect("File is read correctly")
+        .parse()
+        .unwrap()
+}
+
+fn single_run(c: &mut Criterion) {
+    let Computer { state, program } = computer();
+    let compiled = CompiledProgram::new(&program);
+
+    let mut group = c.benchmark_group("single run");
+    group.bench_function("interpreted", |b| {
+        b.iter(|| run(&program, black_box(&state), false))
+    });
+    group.bench_function("compiled", |b| b.iter(|| compiled.run(black_box(&state))));
+    group.finish();
+}
+
+fn brute_force(c: &mut Criterion) {
+    let Computer { state, program } = computer();
+    let compiled = CompiledProgram::new(&program);
+    let values: Vec<u64> = (0..1 << 16).collect();
+
+    let mut group = c.benchmark_group("brute force");
+    group.sample_size(20);
+    group.bench_function(BenchmarkId::new("interpreted", values.len()), |b| {
+        b.iter(|| {
+            values
+                .iter()
+                .map(|&a| run(&program, &State { a, ..state }, false))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function(BenchmarkId::new("compiled", values.len()), |b| {
+        b.iter(|| compiled.run_batch(&state, black_box(&values)))
+    });
+    group.bench_function(BenchmarkId::new("compiled parallel", values.len()), |b| {
+        b.iter(|| compiled.par_run_batch(&state, black_box(&values)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, single_run, brute_force);
+criterion_main!(benches);
@@ -0,0 +1,200 @@
+//! Puzzle answers.
+//!
+//! An [`Answer`] keeps the value a solution came up with in its natural shape so the runner can
+//! compare numbers as numbers, while [`Display`](fmt::Display) always gives the exact string we
+//! would submit. Solutions can attach a rendering (a map, a picture of the robots) or a short
+//! explanation which the runner shows underneath instead of the solution printing it itself.
+
+use std::fmt;
+
+use itertools::Itertools;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i64),
+    Text(String),
+    Coord(i64, i64),
+    /// Submitted as the items joined with commas
+    List(Vec<String>),
+    Annotated {
+        answer: Box<Answer>,
+        rendering: Option<String>,
+        explanation: Option<String>,
+    },
+}
+
+impl Answer {
+    /// A list answer from anything which can be displayed
+    pub fn list<T: fmt::Display>(items: impl IntoIterator<Item = T>) -> Answer {
+        Answer::List(items.into_iter().map(|item| item.to_string()).collect())
+    }
+
+    /// The answer without any rendering or explanation attached
+    pub fn value(&self) -> &Answer {
+        match self {
+            Answer::Annotated { answer, .. } => answer.value(),
+            answer => answer,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self.value() {
+            Answer::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn rendering(&self) -> Option<&str> {
+        match self {
+            Answer::Annotated { rendering, .. } => rendering.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn explanation(&self) -> Option<&str> {
+        match self {
+            Answer::Annotated { explanation, .. } => explanation.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn with_rendering(self, rendering: impl Into<String>) -> Answer {
+        let explanation = self.explanation().map(str::to_string);
+        Answer::Annotated {
+            answer: Box::new(self.value().clone()),
+            rendering: Some(rendering.into()),
+            explanation,
+        }
+    }
+
+    pub fn with_explanation(self, explanation: impl Into<String>) -> Answer {
+        let rendering = self.rendering().map(str::to_string);
+        Answer::Annotated {
+            answer: Box::new(self.value().clone()),
+            rendering,
+            explanation: Some(explanation.into()),
+        }
+    }
+
+    /// Whether this is the answer written in `expected`. Numbers are compared by value, so
+    /// `"+12"` or `" 12"` still match `Int(12)`.
+    pub fn matches(&self, expected: &str) -> bool {
+        let expected = expected.trim();
+        match self.value() {
+            Answer::Int(n) => expected.trim_start_matches('+').parse::<i64>() == Ok(*n),
+            answer => *answer == expected,
+        }
+    }
+
+    /// The submitted answer followed by any rendering and explanation, one per line
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        if let Some(rendering) = self.rendering() {
+            report.push('\n');
+            report.push_str(rendering.trim_end());
+        }
+        if let Some(explanation) = self.explanation() {
+            report.push('\n');
+            report.push_str(explanation.trim_end());
+        }
+
+        report
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::Text(text) => write!(f, "{text}"),
+            Answer::Coord(x, y) => write!(f, "{x},{y}"),
+            Answer::List(items) => write!(f, "{}", items.iter().join(",")),
+            Answer::Annotated { answer, .. } => write!(f, "{answer}"),
+        }
+    }
+}
+
+/// Numbers too big for an `i64` are kept as their digits, so they're still submitted exactly
+macro_rules! int_answer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Answer {
+                    i64::try_from(n).map_or_else(|_| Answer::Text(n.to_string()), Answer::Int)
+                }
+            }
+        )*
+    };
+}
+
+int_answer!(i32, i64, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Answer {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Answer {
+        Answer::Text(text.to_string())
+    }
+}
+
+impl PartialEq<i64> for Answer {
+    fn eq(&self, other: &i64) -> bool {
+        self.as_int() == Some(*other)
+    }
+}
+
+impl PartialEq<&str> for Answer {
+    fn eq(&self, other: &&str) -> bool {
+        let submitted = self.to_string();
+        submitted == *other
+    }
+}
+
+impl PartialEq<String> for Answer {
+    fn eq(&self, other: &String) -> bool {
+        *self == other.as_str()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn submitted_strings() {
+        assert_eq!(Answer::from(42_u64).to_string(), "42");
+        assert_eq!(Answer::from(u64::MAX).to_string(), "18446744073709551615");
+        assert!(Answer::from(u64::MAX).matches("18446744073709551615"));
+        assert_eq!(Answer::Coord(6, 1).to_string(), "6,1");
+        assert_eq!(Answer::list(["co", "de", "ka"]).to_string(), "co,de,ka");
+        assert_eq!(Answer::list([4, 6, 3]).to_string(), "4,6,3");
+        assert_eq!(Answer::from(7_usize).with_rendering("#.#").to_string(), "7");
+    }
+
+    #[test]
+    fn numeric_comparisons() {
+        let answer = Answer::from(1234_i32);
+        assert_eq!(answer, 1234);
+        assert!(answer.matches("1234\n"));
+        assert!(answer.matches("+1234"));
+        assert!(!answer.matches("1235"));
+        assert!(!Answer::from("1234x").matches("1234"));
+        assert_eq!(answer.with_explanation("because").as_int(), Some(1234));
+    }
+
+    #[test]
+    fn annotations() {
+        let answer = Answer::from(3_u32)
+            .with_rendering("..#\n")
+            .with_explanation("three");
+
+        assert_eq!(answer.value(), &Answer::Int(3));
+        assert_eq!(answer.rendering(), Some("..#\n"));
+        assert_eq!(answer.report(), "3\n..#\nthree");
+        assert_eq!(Answer::Int(3).report(), "3");
+    }
+}
@@ -0,0 +1,89 @@
+//! Cooperative cancellation for slow solutions.
+//!
+//! The runner hands every part a [`CancelToken`]. Searches which can take a long time check it as
+//! they go and bail out with [`Cancelled`], everything else just ignores it.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Shared flag plus an optional deadline. Clones share the flag, so cancelling any of them
+/// cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// A token which only fires when [`CancelToken::cancel`] is called
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// A token which also fires once `timeout` has passed
+    pub fn with_timeout(timeout: Duration) -> CancelToken {
+        CancelToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: Some(Instant::now() + timeout),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// `Err(Cancelled)` once the token has fired, so loops can just `cancel.check()?`
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cancelling_is_shared() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert_eq!(clone.check(), Ok(()));
+
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert_eq!(clone.check(), Err(Cancelled));
+    }
+
+    #[test]
+    fn deadlines_expire() {
+        assert!(!CancelToken::with_timeout(Duration::from_secs(60)).is_cancelled());
+        assert!(CancelToken::with_timeout(Duration::ZERO).is_cancelled());
+    }
+}
@@ -1,18 +1,30 @@
-use crate::solution::Solution;
+//! Day 7: Bridge Repair
 
-fn concat(lhs: u64, rhs: u64) -> u64 {
+use std::str::FromStr;
+
+use crate::{
+    answer::Answer,
+    parse::{self, Line, ParseError},
+    solution::Solution,
+};
+
+/// Joins the digits of two numbers, `concat(12, 345) == 12345`
+pub fn concat(lhs: u64, rhs: u64) -> u64 {
     let size = rhs.ilog10() + 1;
-    (lhs * (10 as u64).pow(size)) + rhs
+    (lhs * 10_u64.pow(size)) + rhs
 }
 
-#[derive(Debug, Clone)]
-struct Equation {
-    result: u64,
-    terms: Vec<u64>,
+/// A calibration equation with its operators missing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation {
+    pub result: u64,
+    pub terms: Vec<u64>,
 }
 
 impl Equation {
-    fn is_valid(target: u64, current: u64, terms: &[u64], use_concat: bool) -> bool {
+    /// Whether the remaining `terms` can be combined left to right onto `current` to reach
+    /// `target`, using `+`, `*` and optionally `||` (see [`concat`])
+    pub fn is_valid(target: u64, current: u64, terms: &[u64], use_concat: bool) -> bool {
         if current > target {
             return false;
         }
@@ -31,40 +43,73 @@ impl Equation {
 
         result
     }
+
+    pub fn is_solvable(&self, use_concat: bool) -> bool {
+        Equation::is_valid(self.result, 0, &self.terms, use_concat)
+    }
 }
 
-fn parse_input(puzzle_input: String) -> Vec<Equation> {
-    let mut equations = Vec::new();
-    for line in puzzle_input.lines() {
-        let (result, terms) = line.split_once(": ").unwrap();
+impl FromStr for Equation {
+    type Err = ParseError;
 
-        equations.push(Equation {
-            result: result.parse().unwrap(),
-            terms: terms.split(" ").map(|t| t.parse().unwrap()).collect(),
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (result, terms) = Line::new(s).split_once(": ")?;
+
+        Ok(Equation {
+            result: result.parse()?,
+            terms: terms.ints()?,
         })
     }
-
-    equations
 }
 
 pub struct BridgeSolution;
 
 impl Solution for BridgeSolution {
-    fn part1(puzzle_input: String) -> String {
-        let eqns = parse_input(puzzle_input);
+    fn part1(puzzle_input: String) -> Answer {
+        let eqns: Vec<Equation> = parse::parse_lines(&puzzle_input).unwrap();
 
         eqns.iter()
-            .filter_map(|e| Equation::is_valid(e.result, 0, &e.terms, false).then_some(e.result))
+            .filter_map(|e| e.is_solvable(false).then_some(e.result))
             .sum::<u64>()
-            .to_string()
+            .into()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let eqns = parse_input(puzzle_input);
+    fn part2(puzzle_input: String) -> Answer {
+        let eqns: Vec<Equation> = parse::parse_lines(&puzzle_input).unwrap();
 
         eqns.iter()
-            .filter_map(|e| Equation::is_valid(e.result, 0, &e.terms, true).then_some(e.result))
+            .filter_map(|e| e.is_solvable(true).then_some(e.result))
             .sum::<u64>()
-            .to_string()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_equations() {
+        let eqn: Equation = "3267: 81 40 27".parse().unwrap();
+        assert_eq!(
+            eqn,
+            Equation {
+                result: 3267,
+                terms: vec![81, 40, 27]
+            }
+        );
+
+        assert!("3267 81 40 27".parse::<Equation>().is_err());
+    }
+
+    #[test]
+    fn solves_equations() {
+        assert!("3267: 81 40 27"
+            .parse::<Equation>()
+            .unwrap()
+            .is_solvable(false));
+        assert!(!"156: 15 6".parse::<Equation>().unwrap().is_solvable(false));
+        assert!("156: 15 6".parse::<Equation>().unwrap().is_solvable(true));
+        assert_eq!(concat(12, 345), 12345);
     }
 }
@@ -0,0 +1,200 @@
+//! Assembler for the 3-bit computer, the inverse of [`super::disasm`].
+//!
+//! One instruction per line, with optional labels and `;` comments:
+//!
+//! ```text
+//! start:          ; labels can be on their own line
+//!     adv 3       ; combo operands are 0-3 or a register name
+//!     out A
+//!     jnz start   ; jump targets can be labels or literals
+//! ```
+
+use std::collections::HashMap;
+
+use crate::parse::{self, Line, ParseResult};
+
+use super::{ComboOperand, Op, Program};
+
+fn parse_op(line: &Line, mnemonic: &str) -> ParseResult<Op> {
+    (0..8)
+        .filter_map(Op::decode)
+        .find(|op| op.mnemonic() == mnemonic.to_ascii_lowercase())
+        .ok_or_else(|| line.error(format!("unknown instruction '{mnemonic}'")))
+}
+
+fn parse_literal(line: &Line, operand: &str) -> ParseResult<u8> {
+    let value: u64 = operand
+        .parse()
+        .map_err(|_| line.error(format!("invalid operand '{operand}'")))?;
+
+    u8::try_from(value)
+        .ok()
+        .filter(|value| *value <= 7)
+        .ok_or_else(|| line.error(format!("operand {value} doesn't fit in 3 bits")))
+}
+
+fn parse_combo(line: &Line, operand: &str) -> ParseResult<u8> {
+    let code = match operand.to_ascii_uppercase().as_str() {
+        "A" => 4,
+        "B" => 5,
+        "C" => 6,
+        _ => parse_literal(line, operand)?,
+    };
+
+    match ComboOperand::decode(code) {
+        Some(_) => Ok(code),
+        None => Err(line.error("combo operand 7 is reserved")),
+    }
+}
+
+/// Splits a line into its label (if any) and the instruction text, with comments removed
+fn split_label<'a>(line: &Line<'a>) -> (Option<&'a str>, &'a str) {
+    let code = line.text.split(';').next().unwrap_or_default().trim();
+
+    match code.split_once(':') {
+        Some((label, rest)) => (Some(label.trim()), rest.trim()),
+        None => (None, code),
+    }
+}
+
+/// Assembles `source` into a program, reporting the line of the first mistake
+pub fn assemble(source: &str) -> ParseResult<Program> {
+    // First pass to find where every label points
+    let mut labels = HashMap::new();
+    let mut address = 0;
+    for line in parse::lines(source) {
+        let (label, code) = split_label(&line);
+        if let Some(label) = label {
+            if label.is_empty() || label.contains(char::is_whitespace) {
+                return Err(line.error(format!("invalid label '{label}'")));
+            }
+            if labels.insert(label, address).is_some() {
+                return Err(line.error(format!("label '{label}' is defined twice")));
+            }
+        }
+        if !code.is_empty() {
+            address += 2;
+        }
+    }
+
+    let mut program = Vec::new();
+    for line in parse::lines(source) {
+        let (_, code) = split_label(&line);
+        if code.is_empty() {
+            continue;
+        }
+
+        let mut words = code.split_whitespace();
+        let op = parse_op(&line, words.next().unwrap())?;
+        let operand = words.next();
+        if let Some(extra) = words.next() {
+            return Err(line.error(format!("unexpected '{extra}' after the operand")));
+        }
+
+        let operand = match (op, operand) {
+            (Op::Bxc, None) => 0,
+            (_, None) => return Err(line.error(format!("{} needs an operand", op.mnemonic()))),
+            (Op::Jnz, Some(target)) => match labels.get(target) {
+                Some(&address) => u8::try_from(address)
+                    .ok()
+                    .filter(|address| *address <= 7)
+                    .ok_or_else(|| {
+                        line.error(format!(
+                            "label '{target}' is at address {address}, jnz can only reach 0-7"
+                        ))
+                    })?,
+                None if target.starts_with(|c: char| c.is_ascii_digit()) => {
+                    parse_literal(&line, target)?
+                }
+                None => return Err(line.error(format!("unknown label '{target}'"))),
+            },
+            (op, Some(operand)) if op.takes_combo() => parse_combo(&line, operand)?,
+            (_, Some(operand)) => parse_literal(&line, operand)?,
+        };
+
+        program.push(op as u8);
+        program.push(operand);
+    }
+
+    Ok(program)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{disasm, run, Computer, State};
+    use super::*;
+
+    #[test]
+    fn assembles_programs() {
+        let program = assemble(
+            "
+            ; the example from part 2
+            start:
+                adv 3
+                out A       ; prints A % 8
+                jnz start
+            ",
+        )
+        .unwrap();
+        assert_eq!(program, vec![0, 3, 5, 4, 3, 0]);
+
+        let state = State {
+            a: 2024,
+            b: 0,
+            c: 0,
+        };
+        let (output, _) = run(&program, &state, false).unwrap();
+        assert_eq!(output, vec![5, 7, 3, 0]);
+
+        assert_eq!(
+            assemble("loop: bxc\nbxl 7\njnz loop").unwrap(),
+            vec![4, 0, 1, 7, 3, 0]
+        );
+    }
+
+    #[test]
+    fn round_trips_disassembly() {
+        let program = vec![2, 4, 1, 1, 7, 5, 4, 7, 1, 4, 0, 3, 5, 5, 3, 0];
+        let source: String = disasm::disassemble(&program)
+            .instructions()
+            .map(|instruction| instruction.mnemonic() + "\n")
+            .collect();
+
+        // bxc ignores its operand so the disassembly drops it, it comes back as 0
+        let mut expected = program.clone();
+        expected[7] = 0;
+        assert_eq!(assemble(&source).unwrap(), expected);
+    }
+
+    #[test]
+    fn writes_puzzle_format() {
+        let computer = Computer {
+            state: State { a: 729, b: 0, c: 0 },
+            program: assemble("adv 1\nout A\njnz 0").unwrap(),
+        };
+
+        let text = computer.to_string();
+        assert_eq!(
+            text,
+            "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n"
+        );
+        assert_eq!(text.parse::<Computer>().unwrap(), computer);
+    }
+
+    #[test]
+    fn reports_mistakes() {
+        let error = |source| assemble(source).unwrap_err().to_string();
+
+        assert_eq!(error("adv 3\nout 7"), "line 2: combo operand 7 is reserved");
+        assert_eq!(error("bxl 8"), "line 1: operand 8 doesn't fit in 3 bits");
+        assert_eq!(error("adv\n"), "line 1: adv needs an operand");
+        assert_eq!(error("add 1"), "line 1: unknown instruction 'add'");
+        assert_eq!(error("jnz nowhere"), "line 1: unknown label 'nowhere'");
+        assert_eq!(error("a:\na: bxc"), "line 2: label 'a' is defined twice");
+        assert_eq!(
+            error("bxc\nbxc\nbxc\nbxc\nend: bxc\njnz end"),
+            "line 6: label 'end' is at address 8, jnz can only reach 0-7"
+        );
+        assert_eq!(error("out D"), "line 1: invalid operand 'D'");
+    }
+}
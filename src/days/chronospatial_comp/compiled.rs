@@ -0,0 +1,248 @@
+//! Programs decoded once up front, for running the same program over lots of starting values.
+//!
+//! [`Machine`] decodes every instruction as it reaches it and keeps a full trace's worth of state
+//! around, which is what you want when stepping through a program but adds up when brute forcing
+//! millions of values of A. A [`CompiledProgram`] resolves every operand ahead of time and only
+//! looks at the output when something is printed.
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use super::{divide, ComboOperand, Machine, Op, State, VmError, STEP_LIMIT};
+
+/// Where an operand's value comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Literal(u64),
+    A,
+    B,
+    C,
+}
+
+impl Source {
+    fn read(self, state: &State) -> u64 {
+        match self {
+            Source::Literal(x) => x,
+            Source::A => state.a,
+            Source::B => state.b,
+            Source::C => state.c,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Adv(Source),
+    Bxl(u64),
+    Bst(Source),
+    Jnz(usize),
+    Bxc,
+    Out(Source),
+    Bdv(Source),
+    Cdv(Source),
+}
+
+/// A program with every address decoded ahead of time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledProgram {
+    /// Indexed by address. Jumps can land on odd addresses so every word gets decoded, and
+    /// decoding errors are kept until something actually runs them.
+    instructions: Vec<Result<Instruction, VmError>>,
+    step_limit: u64,
+}
+
+impl CompiledProgram {
+    pub fn new(program: &[u8]) -> CompiledProgram {
+        let empty = State { a: 0, b: 0, c: 0 };
+        let instructions = (0..program.len())
+            .map(|ip| {
+                let machine = Machine {
+                    ip,
+                    ..Machine::new(program, empty.clone())
+                };
+                let (op, operand) = machine.decode()?.expect("ip is inside the program");
+
+                let source = || match ComboOperand::decode(operand) {
+                    Some(ComboOperand::Literal(x)) => Ok(Source::Literal(x as u64)),
+                    Some(ComboOperand::RegisterA) => Ok(Source::A),
+                    Some(ComboOperand::RegisterB) => Ok(Source::B),
+                    Some(ComboOperand::RegisterC) => Ok(Source::C),
+                    None => Err(VmError::ReservedOperand { ip }),
+                };
+                Ok(match op {
+                    Op::Adv => Instruction::Adv(source()?),
+                    Op::Bxl => Instruction::Bxl(operand as u64),
+                    Op::Bst => Instruction::Bst(source()?),
+                    Op::Jnz => Instruction::Jnz(operand as usize),
+                    Op::Bxc => Instruction::Bxc,
+                    Op::Out => Instruction::Out(source()?),
+                    Op::Bdv => Instruction::Bdv(source()?),
+                    Op::Cdv => Instruction::Cdv(source()?),
+                })
+            })
+            .collect();
+
+        CompiledProgram {
+            instructions,
+            step_limit: STEP_LIMIT,
+        }
+    }
+
+    /// Steps a run gets before it fails with [`VmError::StepLimit`], [`STEP_LIMIT`] by default
+    pub fn with_step_limit(mut self, limit: u64) -> CompiledProgram {
+        self.step_limit = limit;
+        self
+    }
+
+    /// Runs until the program halts or `on_output` returns false
+    fn execute(
+        &self,
+        state: &mut State,
+        mut on_output: impl FnMut(u8) -> bool,
+    ) -> Result<(), VmError> {
+        let mut ip = 0;
+        let mut steps = 0;
+
+        while let Some(instruction) = self.instructions.get(ip) {
+            if steps == self.step_limit {
+                return Err(VmError::StepLimit { steps });
+            }
+            steps += 1;
+            ip += 2;
+
+            match (*instruction)? {
+                Instruction::Adv(source) => state.a = divide(state.a, source.read(state)),
+                Instruction::Bxl(value) => state.b ^= value,
+                Instruction::Bst(source) => state.b = source.read(state) % 8,
+                Instruction::Jnz(target) => {
+                    if state.a != 0 {
+                        ip = target;
+                    }
+                }
+                Instruction::Bxc => state.b ^= state.c,
+                Instruction::Out(source) => {
+                    if !on_output((source.read(state) % 8) as u8) {
+                        return Ok(());
+                    }
+                }
+                Instruction::Bdv(source) => state.b = divide(state.a, source.read(state)),
+                Instruction::Cdv(source) => state.c = divide(state.a, source.read(state)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Same as [`super::run`] without the quine check
+    pub fn run(&self, state: &State) -> Result<(Vec<u8>, State), VmError> {
+        let mut state = state.clone();
+        let mut output = Vec::new();
+        self.execute(&mut state, |value| {
+            output.push(value);
+            true
+        })?;
+
+        Ok((output, state))
+    }
+
+    /// Whether starting from `state` prints exactly `target`, stopping at the first wrong output
+    pub fn produces(&self, state: &State, target: &[u8]) -> Result<bool, VmError> {
+        let mut state = state.clone();
+        let mut printed = 0;
+        let mut matches = true;
+        self.execute(&mut state, |value| {
+            matches = target.get(printed) == Some(&value);
+            printed += 1;
+            matches
+        })?;
+
+        Ok(matches && printed == target.len())
+    }
+
+    /// The output for each starting value of register A, with B and C taken from `state`
+    pub fn run_batch(&self, state: &State, values: &[u64]) -> Vec<Result<Vec<u8>, VmError>> {
+        values
+            .iter()
+            .map(|&a| self.run(&State { a, ..*state }).map(|(output, _)| output))
+            .collect()
+    }
+
+    /// [`CompiledProgram::run_batch`] spread over all cores
+    pub fn par_run_batch(&self, state: &State, values: &[u64]) -> Vec<Result<Vec<u8>, VmError>> {
+        values
+            .par_iter()
+            .map(|&a| self.run(&State { a, ..*state }).map(|(output, _)| output))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{asm::assemble, run};
+    use super::*;
+
+    const REAL: [u8; 16] = [2, 4, 1, 1, 7, 5, 4, 7, 1, 4, 0, 3, 5, 5, 3, 0];
+
+    #[test]
+    fn matches_the_interpreter() {
+        let program = REAL.to_vec();
+        let compiled = CompiledProgram::new(&program);
+
+        for a in [0, 1, 7, 8, 729, 2024, 117440, 164432426, u64::MAX] {
+            let state = State { a, b: 3, c: 9 };
+            assert_eq!(compiled.run(&state), run(&program, &state, false));
+        }
+    }
+
+    #[test]
+    fn runs_batches() {
+        let program = assemble("adv 1\nout A\njnz 0").unwrap();
+        let compiled = CompiledProgram::new(&program);
+        let state = State { a: 0, b: 0, c: 0 };
+
+        let values: Vec<u64> = (0..1000).collect();
+        let outputs = compiled.run_batch(&state, &values);
+        assert_eq!(outputs[729], Ok(vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]));
+        assert_eq!(compiled.par_run_batch(&state, &values), outputs);
+    }
+
+    #[test]
+    fn checks_outputs() {
+        let program = vec![0, 3, 5, 4, 3, 0];
+        let compiled = CompiledProgram::new(&program);
+        let state = State {
+            a: 117440,
+            b: 0,
+            c: 0,
+        };
+
+        assert_eq!(compiled.produces(&state, &program), Ok(true));
+        assert_eq!(compiled.produces(&state, &program[..5]), Ok(false));
+        assert_eq!(
+            compiled.produces(&State { a: 0, ..state }, &program),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn keeps_decoding_errors_until_they_run() {
+        // The bad word is jumped over unless A is 0
+        let compiled = CompiledProgram::new(&[5, 4, 3, 6, 9, 0]);
+        let state = State { a: 1, b: 0, c: 0 };
+        assert_eq!(compiled.run(&state), Ok((vec![1], state.clone())));
+
+        assert_eq!(
+            compiled.run(&State { a: 0, ..state }),
+            Err(VmError::InvalidOpcode { ip: 4, code: 9 })
+        );
+        assert_eq!(
+            CompiledProgram::new(&[3, 0]).run(&State { a: 1, ..state }),
+            Err(VmError::StepLimit { steps: STEP_LIMIT })
+        );
+        assert_eq!(
+            CompiledProgram::new(&[3, 0])
+                .with_step_limit(10)
+                .run(&State { a: 1, ..state }),
+            Err(VmError::StepLimit { steps: 10 })
+        );
+    }
+}
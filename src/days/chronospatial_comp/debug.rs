@@ -0,0 +1,202 @@
+//! A small debugger for the 3-bit computer.
+//!
+//! Breakpoints can be set on an instruction address or on a predicate over the registers, and a
+//! step limit stops programs which never halt:
+//!
+//! ```text
+//! let mut debugger = Debugger::new(&program, state).with_step_limit(10_000);
+//! debugger.break_at(12);
+//! while let Stop::Breakpoint(_) = debugger.resume() {
+//!     println!("{:?}", debugger.machine.state);
+//! }
+//! ```
+
+use super::{Machine, State, TraceStep, VmError};
+
+/// Why [`Debugger::resume`] handed control back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// About to execute the instruction at this address
+    Breakpoint(usize),
+    /// The register predicate with this index became true
+    Predicate(usize),
+    Halted,
+    /// The step limit was reached before the program halted
+    StepLimit,
+    /// The instruction at `ip` couldn't run, `ip` is left pointing at it
+    Error(VmError),
+}
+
+type Predicate = Box<dyn Fn(&State) -> bool>;
+
+pub struct Debugger<'p> {
+    pub machine: Machine<'p>,
+    breakpoints: Vec<usize>,
+    predicates: Vec<Predicate>,
+    step_limit: Option<u64>,
+    /// Every step executed so far, when tracing is on
+    pub trace: Option<Vec<TraceStep>>,
+}
+
+impl<'p> Debugger<'p> {
+    pub fn new(program: &'p [u8], state: State) -> Debugger<'p> {
+        Debugger {
+            machine: Machine::new(program, state),
+            breakpoints: Vec::new(),
+            predicates: Vec::new(),
+            step_limit: None,
+            trace: None,
+        }
+    }
+
+    /// Stops with [`Stop::StepLimit`] once `limit` instructions have run
+    pub fn with_step_limit(mut self, limit: u64) -> Debugger<'p> {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// Records every executed step in [`Debugger::trace`]
+    pub fn with_trace(mut self) -> Debugger<'p> {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    /// Stops before the instruction at `ip` executes
+    pub fn break_at(&mut self, ip: usize) {
+        self.breakpoints.push(ip);
+    }
+
+    /// Stops after any step which leaves the registers matching `predicate`. Returns the index
+    /// reported in [`Stop::Predicate`].
+    pub fn break_when(&mut self, predicate: impl Fn(&State) -> bool + 'static) -> usize {
+        self.predicates.push(Box::new(predicate));
+        self.predicates.len() - 1
+    }
+
+    /// Executes a single instruction, ignoring breakpoints
+    pub fn step(&mut self) -> Result<Option<TraceStep>, VmError> {
+        let step = self.machine.step()?;
+        if let (Some(trace), Some(step)) = (&mut self.trace, &step) {
+            trace.push(step.clone());
+        }
+
+        Ok(step)
+    }
+
+    /// Runs until a breakpoint, predicate or the step limit stops it, or the program halts.
+    /// Always executes at least one instruction so resuming from a breakpoint moves past it.
+    pub fn resume(&mut self) -> Stop {
+        let mut first = true;
+        loop {
+            if self.machine.is_halted() {
+                return Stop::Halted;
+            }
+            if self
+                .step_limit
+                .is_some_and(|limit| self.machine.steps >= limit)
+            {
+                return Stop::StepLimit;
+            }
+            if !first && self.breakpoints.contains(&self.machine.ip) {
+                return Stop::Breakpoint(self.machine.ip);
+            }
+            first = false;
+
+            if let Err(err) = self.step() {
+                return Stop::Error(err);
+            }
+
+            let state = &self.machine.state;
+            if let Some(index) = self.predicates.iter().position(|p| p(state)) {
+                return Stop::Predicate(index);
+            }
+        }
+    }
+
+    /// Runs from the start until the first stop, so a breakpoint on address 0 is honoured
+    pub fn start(&mut self) -> Stop {
+        if self.machine.steps == 0 && self.breakpoints.contains(&self.machine.ip) {
+            return Stop::Breakpoint(self.machine.ip);
+        }
+
+        self.resume()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::asm::assemble;
+    use super::*;
+
+    fn example() -> Vec<u8> {
+        assemble("start: adv 1\nout A\njnz start").unwrap()
+    }
+
+    #[test]
+    fn stops_at_breakpoints() {
+        let program = example();
+        let mut debugger = Debugger::new(&program, State { a: 729, b: 0, c: 0 });
+        debugger.break_at(2);
+
+        assert_eq!(debugger.start(), Stop::Breakpoint(2));
+        assert_eq!(debugger.machine.state.a, 364);
+        assert_eq!(debugger.resume(), Stop::Breakpoint(2));
+        assert_eq!(debugger.machine.output, vec![4]);
+        assert_eq!(debugger.machine.state.a, 182);
+
+        let mut debugger = Debugger::new(&program, State { a: 729, b: 0, c: 0 });
+        debugger.break_at(0);
+        assert_eq!(debugger.start(), Stop::Breakpoint(0));
+        assert_eq!(debugger.machine.steps, 0);
+    }
+
+    #[test]
+    fn stops_on_predicates() {
+        let program = example();
+        let mut debugger = Debugger::new(&program, State { a: 729, b: 0, c: 0 });
+        let small = debugger.break_when(|state| state.a < 10);
+
+        assert_eq!(debugger.start(), Stop::Predicate(small));
+        assert_eq!(debugger.machine.state.a, 5);
+        assert_eq!(debugger.resume(), Stop::Predicate(small));
+        assert_eq!(debugger.machine.ip, 4);
+    }
+
+    #[test]
+    fn runs_to_completion() {
+        let program = example();
+        let mut debugger = Debugger::new(&program, State { a: 10, b: 0, c: 0 }).with_trace();
+
+        assert_eq!(debugger.start(), Stop::Halted);
+        assert_eq!(debugger.machine.output, vec![5, 2, 1, 0]);
+
+        let trace = debugger.trace.unwrap();
+        assert_eq!(trace.len() as u64, debugger.machine.steps);
+        assert_eq!(trace[0].to_string(), "00: adv 1  A=5 B=0 C=0");
+        assert_eq!(trace[1].to_string(), "02: out A  A=5 B=0 C=0 out 5");
+    }
+
+    #[test]
+    fn limits_runaway_programs() {
+        // B never changes A, so this spins forever
+        let program = assemble("spin: bxl 1\njnz spin").unwrap();
+        let mut debugger = Debugger::new(&program, State { a: 1, b: 0, c: 0 }).with_step_limit(100);
+
+        assert_eq!(debugger.start(), Stop::StepLimit);
+        assert_eq!(debugger.machine.steps, 100);
+        assert_eq!(debugger.resume(), Stop::StepLimit);
+    }
+
+    #[test]
+    fn stops_on_errors() {
+        let program = vec![1, 2, 9, 0];
+        let mut debugger = Debugger::new(&program, State { a: 0, b: 0, c: 0 });
+
+        assert_eq!(
+            debugger.start(),
+            Stop::Error(VmError::InvalidOpcode { ip: 2, code: 9 })
+        );
+        assert_eq!(debugger.machine.ip, 2);
+        assert_eq!(debugger.machine.state.b, 2);
+    }
+}
@@ -0,0 +1,246 @@
+//! Disassembler for the 3-bit computer, so puzzle programs can be read instead of decoded by hand.
+//!
+//! ```text
+//! loop 00..=14 while A != 0
+//!     00: 2,4  bst A   ; B = A % 8
+//!     02: 1,1  bxl 1   ; B = B ^ 1
+//!     ...
+//!     14: 3,0  jnz 0   ; if A != 0 goto 00
+//! ```
+
+use std::fmt;
+
+use super::{ComboOperand, Op};
+
+/// A decoded instruction and where it sits in the program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub address: usize,
+    pub op: Op,
+    pub operand: u8,
+}
+
+impl Instruction {
+    /// The operand the way the instruction uses it. Combo operands are resolved to register
+    /// names, apart from the reserved 7 which is left as it is, and `bxc` ignores its operand
+    /// entirely.
+    pub fn operand_name(&self) -> String {
+        match self.op {
+            Op::Bxc => String::new(),
+            op if op.takes_combo() => ComboOperand::decode(self.operand)
+                .map_or_else(|| self.operand.to_string(), |combo| combo.name()),
+            _ => self.operand.to_string(),
+        }
+    }
+
+    /// e.g. `adv 3` or `out B`
+    pub fn mnemonic(&self) -> String {
+        match self.op {
+            Op::Bxc => self.op.mnemonic().to_string(),
+            op => format!("{} {}", op.mnemonic(), self.operand_name()),
+        }
+    }
+
+    /// What the instruction does, written out as an assignment
+    pub fn effect(&self) -> String {
+        let operand = self.operand_name();
+        match self.op {
+            Op::Adv => format!("A = A >> {operand}"),
+            Op::Bxl => format!("B = B ^ {operand}"),
+            Op::Bst => format!("B = {operand} % 8"),
+            Op::Jnz => format!("if A != 0 goto {:02}", self.operand),
+            Op::Bxc => "B = B ^ C".to_string(),
+            Op::Out => format!("out {operand} % 8"),
+            Op::Bdv => format!("B = A >> {operand}"),
+            Op::Cdv => format!("C = A >> {operand}"),
+        }
+    }
+}
+
+/// One entry in a disassembly, either an instruction or a word which can't be executed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decoded {
+    Instruction(Instruction),
+    Invalid {
+        address: usize,
+        code: Vec<u8>,
+        reason: &'static str,
+    },
+}
+
+impl Decoded {
+    pub fn address(&self) -> usize {
+        match self {
+            Decoded::Instruction(instruction) => instruction.address,
+            Decoded::Invalid { address, .. } => *address,
+        }
+    }
+}
+
+/// A backwards `jnz`, the body runs from `start` up to and including the jump at `end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Loop {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disassembly {
+    pub lines: Vec<Decoded>,
+    pub loops: Vec<Loop>,
+}
+
+impl Disassembly {
+    pub fn instructions(&self) -> impl Iterator<Item = &Instruction> {
+        self.lines.iter().filter_map(|line| match line {
+            Decoded::Instruction(instruction) => Some(instruction),
+            _ => None,
+        })
+    }
+}
+
+/// Decodes `program` two words at a time, the way the computer reads it when it never jumps to
+/// an odd address
+pub fn disassemble(program: &[u8]) -> Disassembly {
+    let mut lines = Vec::new();
+    for (i, words) in program.chunks(2).enumerate() {
+        let address = i * 2;
+        let invalid = |reason| Decoded::Invalid {
+            address,
+            code: words.to_vec(),
+            reason,
+        };
+
+        let line = match (Op::decode(words[0]), words.get(1)) {
+            (None, _) => invalid("invalid opcode"),
+            (Some(_), None) => invalid("missing operand"),
+            (Some(_), Some(&operand)) if operand > 7 => invalid("operand out of range"),
+            (Some(op), Some(&7)) if op.takes_combo() => invalid("reserved combo operand 7"),
+            (Some(op), Some(&operand)) => Decoded::Instruction(Instruction {
+                address,
+                op,
+                operand,
+            }),
+        };
+        lines.push(line);
+    }
+
+    let mut loops: Vec<Loop> = lines
+        .iter()
+        .filter_map(|line| match line {
+            Decoded::Instruction(Instruction {
+                address,
+                op: Op::Jnz,
+                operand,
+            }) if (*operand as usize) <= *address => Some(Loop {
+                start: *operand as usize,
+                end: *address,
+            }),
+            _ => None,
+        })
+        .collect();
+    // Outer loops first so they open before the loops nested inside them
+    loops.sort_by_key(|l| (l.start, std::cmp::Reverse(l.end)));
+
+    Disassembly { lines, loops }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            let address = line.address();
+            let enclosing: Vec<_> = self
+                .loops
+                .iter()
+                .filter(|l| l.start <= address && address <= l.end)
+                .collect();
+
+            // Loops are sorted outermost first, so each header is indented by the loops
+            // enclosing it
+            for (depth, l) in enclosing.iter().enumerate() {
+                if l.start == address {
+                    let indent = "    ".repeat(depth);
+                    writeln!(f, "{indent}loop {:02}..={:02} while A != 0", l.start, l.end)?;
+                }
+            }
+
+            let indent = "    ".repeat(enclosing.len());
+            match line {
+                Decoded::Instruction(instruction) => writeln!(
+                    f,
+                    "{indent}{address:02}: {},{}  {:<8}; {}",
+                    instruction.op as u8,
+                    instruction.operand,
+                    instruction.mnemonic(),
+                    instruction.effect()
+                )?,
+                Decoded::Invalid { code, reason, .. } => {
+                    let code: Vec<_> = code.iter().map(u8::to_string).collect();
+                    writeln!(
+                        f,
+                        "{indent}{address:02}: {:<3}  {:<8}; {reason}",
+                        code.join(","),
+                        "???"
+                    )?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decodes_instructions() {
+        let disassembly = disassemble(&[0, 3, 5, 4, 3, 0]);
+        let mnemonics: Vec<_> = disassembly.instructions().map(|i| i.mnemonic()).collect();
+
+        assert_eq!(mnemonics, vec!["adv 3", "out A", "jnz 0"]);
+        assert_eq!(disassembly.loops, vec![Loop { start: 0, end: 4 }]);
+
+        let reserved = Instruction {
+            address: 0,
+            op: Op::Out,
+            operand: 7,
+        };
+        assert_eq!(reserved.mnemonic(), "out 7");
+    }
+
+    #[test]
+    fn renders_loops() {
+        let disassembly = disassemble(&[2, 4, 1, 1, 7, 5, 4, 7, 1, 4, 0, 3, 5, 5, 3, 0]);
+
+        assert_eq!(
+            disassembly.to_string(),
+            "loop 00..=14 while A != 0
+    00: 2,4  bst A   ; B = A % 8
+    02: 1,1  bxl 1   ; B = B ^ 1
+    04: 7,5  cdv B   ; C = A >> B
+    06: 4,7  bxc     ; B = B ^ C
+    08: 1,4  bxl 4   ; B = B ^ 4
+    10: 0,3  adv 3   ; A = A >> 3
+    12: 5,5  out B   ; out B % 8
+    14: 3,0  jnz 0   ; if A != 0 goto 00
+"
+        );
+    }
+
+    #[test]
+    fn flags_invalid_words() {
+        let disassembly = disassemble(&[5, 7, 9, 0, 1, 7, 3]);
+
+        assert_eq!(
+            disassembly.to_string(),
+            "00: 5,7  ???     ; reserved combo operand 7
+02: 9,0  ???     ; invalid opcode
+04: 1,7  bxl 7   ; B = B ^ 7
+06: 3    ???     ; missing operand
+"
+        );
+        assert!(disassembly.loops.is_empty());
+    }
+}
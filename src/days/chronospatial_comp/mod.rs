@@ -0,0 +1,683 @@
+//! Day 17: Chronospatial Computer
+
+use std::{fmt, ops::Range, str::FromStr};
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+pub mod asm;
+pub mod compiled;
+pub mod debug;
+pub mod disasm;
+pub mod quine;
+
+use crate::{
+    answer::Answer,
+    cancel::{CancelToken, Cancelled},
+    parse::{self, ParseError, ParseResult},
+    solution::Solution,
+};
+
+use quine::{QuineError, QuineSolver};
+
+/// The eight instructions of the 3-bit computer, indexed by opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+impl Op {
+    pub fn decode(code: u8) -> Option<Op> {
+        match code {
+            0 => Some(Op::Adv),
+            1 => Some(Op::Bxl),
+            2 => Some(Op::Bst),
+            3 => Some(Op::Jnz),
+            4 => Some(Op::Bxc),
+            5 => Some(Op::Out),
+            6 => Some(Op::Bdv),
+            7 => Some(Op::Cdv),
+            _ => None,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Op::Adv => "adv",
+            Op::Bxl => "bxl",
+            Op::Bst => "bst",
+            Op::Jnz => "jnz",
+            Op::Bxc => "bxc",
+            Op::Out => "out",
+            Op::Bdv => "bdv",
+            Op::Cdv => "cdv",
+        }
+    }
+
+    /// Whether the operand is a combo operand rather than a literal
+    pub fn takes_combo(&self) -> bool {
+        matches!(self, Op::Adv | Op::Bst | Op::Out | Op::Bdv | Op::Cdv)
+    }
+}
+
+/// Operand which is either a literal 0-3 or one of the registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComboOperand {
+    Literal(u8),
+    RegisterA,
+    RegisterB,
+    RegisterC,
+}
+
+impl ComboOperand {
+    /// Decodes a combo operand, 7 is reserved and never valid
+    pub fn decode(code: u8) -> Option<ComboOperand> {
+        match code {
+            x if x <= 3 => Some(ComboOperand::Literal(x)),
+            4 => Some(ComboOperand::RegisterA),
+            5 => Some(ComboOperand::RegisterB),
+            6 => Some(ComboOperand::RegisterC),
+            _ => None,
+        }
+    }
+
+    /// How the operand is written, the literal value or the register name
+    pub fn name(&self) -> String {
+        match self {
+            ComboOperand::Literal(x) => x.to_string(),
+            ComboOperand::RegisterA => "A".to_string(),
+            ComboOperand::RegisterB => "B".to_string(),
+            ComboOperand::RegisterC => "C".to_string(),
+        }
+    }
+
+    pub fn resolve(&self, state: &State) -> u64 {
+        match self {
+            ComboOperand::Literal(x) => *x as u64,
+            ComboOperand::RegisterA => state.a,
+            ComboOperand::RegisterB => state.b,
+            ComboOperand::RegisterC => state.c,
+        }
+    }
+}
+
+/// The three registers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+}
+
+pub type Program = Vec<u8>;
+
+/// A program together with the registers it starts with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Computer {
+    pub state: State,
+    pub program: Program,
+}
+
+impl FromStr for Computer {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [registers, program] = parse::sections_n(s)?;
+
+        let state = State {
+            a: registers.field("Register A")?.parse()?,
+            b: registers.field("Register B")?.parse()?,
+            c: registers.field("Register C")?.parse()?,
+        };
+        let program = program.field("Program")?.ints()?;
+
+        Ok(Computer { state, program })
+    }
+}
+
+impl fmt::Display for Computer {
+    /// Writes the computer back out in the puzzle input format
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let program: Vec<_> = self.program.iter().map(u8::to_string).collect();
+
+        writeln!(f, "Register A: {}", self.state.a)?;
+        writeln!(f, "Register B: {}", self.state.b)?;
+        writeln!(f, "Register C: {}", self.state.c)?;
+        writeln!(f)?;
+        writeln!(f, "Program: {}", program.join(","))
+    }
+}
+
+pub fn parse_input(puzzle_input: &str) -> ParseResult<(State, Program)> {
+    let Computer { state, program } = puzzle_input.parse()?;
+
+    Ok((state, program))
+}
+
+/// What a single instruction did, as reported by [`Machine::step`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    pub ip: usize,
+    pub op: Op,
+    pub operand: u8,
+    /// Registers after the instruction ran
+    pub state: State,
+    pub output: Option<u8>,
+}
+
+impl fmt::Display for TraceStep {
+    /// One line per step with the registers in octal, since the programs work 3 bits at a time
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let instruction = disasm::Instruction {
+            address: self.ip,
+            op: self.op,
+            operand: self.operand,
+        };
+        write!(
+            f,
+            "{:02}: {:<6} A={:o} B={:o} C={:o}",
+            self.ip,
+            instruction.mnemonic(),
+            self.state.a,
+            self.state.b,
+            self.state.c
+        )?;
+        if let Some(value) = self.output {
+            write!(f, " out {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Ways a program can fail to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmError {
+    InvalidOpcode {
+        ip: usize,
+        code: u8,
+    },
+    /// An operand word which isn't a 3-bit number
+    InvalidOperand {
+        ip: usize,
+        operand: u8,
+    },
+    /// Combo operand 7 is reserved and never appears in valid programs
+    ReservedOperand {
+        ip: usize,
+    },
+    /// The last instruction has no operand after it
+    MissingOperand {
+        ip: usize,
+    },
+    /// The program was still running when the step budget ran out
+    StepLimit {
+        steps: u64,
+    },
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::InvalidOpcode { ip, code } => write!(f, "invalid opcode {code} at {ip:02}"),
+            VmError::InvalidOperand { ip, operand } => {
+                write!(f, "operand {operand} at {ip:02} doesn't fit in 3 bits")
+            }
+            VmError::ReservedOperand { ip } => write!(f, "reserved combo operand 7 at {ip:02}"),
+            VmError::MissingOperand { ip } => write!(f, "missing operand at {ip:02}"),
+            VmError::StepLimit { steps } => write!(f, "still running after {steps} steps"),
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
+
+/// `num / 2^power`, which is 0 once the power is past the width of a register rather than
+/// overflowing
+fn divide(num: u64, power: u64) -> u64 {
+    u32::try_from(power)
+        .ok()
+        .and_then(|power| num.checked_shr(power))
+        .unwrap_or(0)
+}
+
+/// The computer part way through running a program
+#[derive(Debug, Clone)]
+pub struct Machine<'p> {
+    pub program: &'p [u8],
+    pub state: State,
+    pub ip: usize,
+    pub output: Vec<u8>,
+    /// Number of instructions executed so far
+    pub steps: u64,
+}
+
+impl<'p> Machine<'p> {
+    pub fn new(program: &'p [u8], state: State) -> Machine<'p> {
+        Machine {
+            program,
+            state,
+            ip: 0,
+            output: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.ip >= self.program.len()
+    }
+
+    /// Reads the instruction at `ip` without running it
+    pub fn decode(&self) -> Result<Option<(Op, u8)>, VmError> {
+        let ip = self.ip;
+        let Some(&code) = self.program.get(ip) else {
+            return Ok(None);
+        };
+
+        let op = Op::decode(code).ok_or(VmError::InvalidOpcode { ip, code })?;
+        let operand = *self
+            .program
+            .get(ip + 1)
+            .ok_or(VmError::MissingOperand { ip })?;
+        if operand > 7 {
+            return Err(VmError::InvalidOperand { ip, operand });
+        }
+        if op.takes_combo() && ComboOperand::decode(operand).is_none() {
+            return Err(VmError::ReservedOperand { ip });
+        }
+
+        Ok(Some((op, operand)))
+    }
+
+    /// Executes the instruction at `ip`, or returns `None` if the program has halted
+    pub fn step(&mut self) -> Result<Option<TraceStep>, VmError> {
+        let ip = self.ip;
+        let Some((op, operand)) = self.decode()? else {
+            return Ok(None);
+        };
+        let combo = || ComboOperand::decode(operand).ok_or(VmError::ReservedOperand { ip });
+        let current_state = &mut self.state;
+        let mut output = None;
+        let mut next_ip = ip + 2;
+
+        match op {
+            Op::Adv => {
+                let power = combo()?.resolve(current_state);
+                current_state.a = divide(current_state.a, power);
+            }
+            Op::Bxl => {
+                let value = current_state.b;
+
+                current_state.b = value ^ operand as u64;
+            }
+            Op::Bst => {
+                current_state.b = combo()?.resolve(current_state).rem_euclid(8);
+            }
+            Op::Jnz => {
+                if current_state.a != 0 {
+                    next_ip = operand as usize;
+                }
+            }
+            Op::Bxc => {
+                current_state.b ^= current_state.c;
+            }
+            Op::Out => {
+                let value = combo()?.resolve(current_state).rem_euclid(8) as u8;
+                output = Some(value);
+            }
+            Op::Bdv => {
+                let power = combo()?.resolve(current_state);
+                current_state.b = divide(current_state.a, power);
+            }
+            Op::Cdv => {
+                let power = combo()?.resolve(current_state);
+                current_state.c = divide(current_state.a, power);
+            }
+        }
+
+        self.output.extend(output);
+        self.ip = next_ip;
+        self.steps += 1;
+
+        Ok(Some(TraceStep {
+            ip,
+            op,
+            operand,
+            state: self.state.clone(),
+            output,
+        }))
+    }
+}
+
+/// Iterating a machine runs it to completion one step at a time
+impl Iterator for Machine<'_> {
+    type Item = Result<TraceStep, VmError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step().transpose()
+    }
+}
+
+/// Steps [`run`] allows before deciding a program never halts. Puzzle programs take a few hundred.
+pub const STEP_LIMIT: u64 = 1_000_000;
+
+/// Runs `program` until it halts, returning the output and the final registers.
+/// With `quine` set it stops as soon as the output stops matching the program.
+pub fn run(program: &Program, state: &State, quine: bool) -> Result<(Vec<u8>, State), VmError> {
+    run_with_limit(program, state, quine, STEP_LIMIT)
+}
+
+/// [`run`] with a different step budget
+pub fn run_with_limit(
+    program: &Program,
+    state: &State,
+    quine: bool,
+    step_limit: u64,
+) -> Result<(Vec<u8>, State), VmError> {
+    let mut machine = Machine::new(program, state.clone());
+
+    while machine.step()?.is_some() {
+        // Check for exit condition
+        if quine
+            && program
+                .iter()
+                .zip(&machine.output)
+                .any(|(prog, out)| prog != out)
+        {
+            break;
+        }
+
+        if machine.steps >= step_limit && !machine.is_halted() {
+            return Err(VmError::StepLimit {
+                steps: machine.steps,
+            });
+        }
+    }
+
+    Ok((machine.output, machine.state))
+}
+
+/// Recursively works backwards to generate a number for part 2. This assumes the program shifts
+/// A by 3 bits each loop, [`QuineSolver`] handles other shapes too.
+pub fn generate_num(program: &Program, state: &State, seed: u64, targets: &[u8]) -> Option<u64> {
+    let target_value = targets.first()?;
+
+    // For each 3 byte num
+    for i in 0..8 {
+        // Try it out in a clean substate
+        let mut sub_state = state.clone();
+        // Slide the existing answer over by 3 to make room
+        let candidate = seed.checked_mul(2_u64.pow(3))? + i;
+        sub_state.a = candidate;
+        // Run the program and check the output
+        let Ok((output, _)) = run(program, &sub_state, true) else {
+            continue;
+        };
+
+        // If we generated the right walue
+        if output.first() == Some(target_value) {
+            // If we have remaining digits to generate
+            if targets.len() > 1 {
+                // Recurse and try to generate the remaining digits
+                // This may fail because the generated bit is affected by the
+                // lowest 7 bits of the input number
+                let recursive_result = generate_num(program, state, candidate, &targets[1..]);
+
+                if recursive_result.is_some() {
+                    return recursive_result;
+                }
+            } else {
+                // Yay! it worked
+                return Some(candidate);
+            }
+        }
+    }
+
+    // If it never worked, return none and stop exploring this bit prefix
+    None
+}
+
+/// Tries every value of register A in `candidates` until the program outputs itself. This works
+/// for any program but is hopeless for real inputs (the answer is around 2^48), so it checks
+/// `cancel` as it goes.
+pub fn search_quine(
+    program: &Program,
+    state: &State,
+    candidates: Range<u64>,
+    cancel: &CancelToken,
+) -> Result<Option<u64>, Cancelled> {
+    let compiled = compiled::CompiledProgram::new(program);
+
+    candidates
+        .into_par_iter()
+        .find_map_first(|a| {
+            if a % 4096 == 0 && cancel.is_cancelled() {
+                return Some(Err(Cancelled));
+            }
+
+            let sub_state = State { a, ..*state };
+            compiled
+                .produces(&sub_state, program)
+                .unwrap_or(false)
+                .then_some(Ok(a))
+        })
+        .transpose()
+}
+
+pub struct ChronospatialSolution;
+
+impl Solution for ChronospatialSolution {
+    fn part1(puzzle_input: String) -> Answer {
+        let (state, program) = parse_input(&puzzle_input).unwrap();
+
+        let (output, _) = run(&program, &state, false).unwrap();
+
+        Answer::list(output)
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        ChronospatialSolution::part2_cancellable(puzzle_input, &CancelToken::new()).unwrap()
+    }
+
+    fn part2_cancellable(puzzle_input: String, cancel: &CancelToken) -> Result<Answer, Cancelled> {
+        let (state, program) = parse_input(&puzzle_input).unwrap();
+
+        // Programs which don't shift A by a constant fall back to counting up, which is hopeless
+        // for real inputs but can be stopped with the cancel token
+        let solver = QuineSolver::new(&program, state).with_search_limit(u64::MAX);
+        // A program without a quine has no answer to give, same as input we can't parse
+        match solver.smallest(&program, cancel) {
+            Ok(result) => Ok(result.into()),
+            Err(QuineError::Cancelled) => Err(Cancelled),
+            Err(err) => panic!("{err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_computer() {
+        let computer: Computer = "Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0"
+            .parse()
+            .unwrap();
+
+        assert_eq!(computer.state, State { a: 729, b: 0, c: 0 });
+        assert_eq!(computer.program, vec![0, 1, 5, 4, 3, 0]);
+
+        let (output, _) = run(&computer.program, &computer.state, false).unwrap();
+        assert_eq!(Answer::list(output), "4,6,3,5,6,3,5,2,1,0");
+
+        let err = "Register A: 729\nRegister B: x\nRegister C: 0\n\nProgram: 0"
+            .parse::<Computer>()
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn generates_quine() {
+        let (state, program) = parse_input(
+            "Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0",
+        )
+        .unwrap();
+        let target: Vec<_> = program.iter().rev().cloned().collect();
+
+        assert_eq!(generate_num(&program, &state, 0, &target), Some(117440));
+        assert_eq!(generate_num(&program, &state, 0, &[]), None);
+        // Never outputs anything
+        assert_eq!(generate_num(&vec![0, 3, 3, 0], &state, 0, &target), None);
+    }
+
+    #[test]
+    fn searches_for_quine() {
+        let state = State { a: 0, b: 0, c: 0 };
+        let program: Program = vec![0, 3, 5, 4, 3, 0];
+
+        assert_eq!(
+            search_quine(&program, &state, 0..200_000, &CancelToken::new()),
+            Ok(Some(117440))
+        );
+        assert_eq!(
+            search_quine(&program, &state, 0..1000, &CancelToken::new()),
+            Ok(None)
+        );
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert_eq!(
+            search_quine(&program, &state, 0..u64::MAX, &cancel),
+            Err(Cancelled)
+        );
+    }
+
+    #[test]
+    fn reports_programs_without_a_quine() {
+        // Always prints 1, so it can never print its own 0s
+        let program = vec![0, 3, 2, 1, 5, 5, 3, 0];
+        let solver = QuineSolver::new(&program, State { a: 0, b: 0, c: 0 });
+
+        assert_eq!(
+            solver.smallest(&program, &CancelToken::new()),
+            Err(QuineError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn test_case_1() {
+        let state = State { a: 0, b: 0, c: 9 };
+        let program: Program = vec![2, 6];
+
+        let (_, out_state) = run(&program, &state, false).unwrap();
+        assert_eq!(out_state.b, 1);
+    }
+
+    #[test]
+    fn test_case_2() {
+        let state = State { a: 10, b: 0, c: 0 };
+        let program: Program = vec![5, 0, 5, 1, 5, 4];
+        let expected_output = vec![0, 1, 2];
+
+        let (output, _) = run(&program, &state, false).unwrap();
+        assert_eq!(output, expected_output);
+    }
+
+    #[test]
+    fn test_case_3() {
+        let state = State {
+            a: 2024,
+            b: 0,
+            c: 0,
+        };
+        let program: Program = vec![0, 1, 5, 4, 3, 0];
+        let expected_output = vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0];
+
+        let (output, out_state) = run(&program, &state, false).unwrap();
+        assert_eq!(out_state.a, 0);
+        assert_eq!(output, expected_output);
+    }
+
+    #[test]
+    fn test_case_4() {
+        let state = State { a: 0, b: 29, c: 0 };
+        let program: Program = vec![1, 7];
+
+        let (_, out_state) = run(&program, &state, false).unwrap();
+        assert_eq!(out_state.b, 26);
+    }
+
+    #[test]
+    fn test_case_5() {
+        let state = State {
+            a: 0,
+            b: 2024,
+            c: 43690,
+        };
+        let program: Program = vec![4, 0];
+
+        let (_, out_state) = run(&program, &state, false).unwrap();
+        assert_eq!(out_state.b, 44354);
+    }
+
+    #[test]
+    fn reports_invalid_programs() {
+        let state = State { a: 1, b: 0, c: 0 };
+        let error = |program: Program| run(&program, &state, false).unwrap_err();
+
+        assert_eq!(
+            error(vec![1, 0, 8, 0]),
+            VmError::InvalidOpcode { ip: 2, code: 8 }
+        );
+        assert_eq!(
+            error(vec![1, 9]),
+            VmError::InvalidOperand { ip: 0, operand: 9 }
+        );
+        assert_eq!(error(vec![5, 7]), VmError::ReservedOperand { ip: 0 });
+        assert_eq!(error(vec![4, 0, 4]), VmError::MissingOperand { ip: 2 });
+        assert_eq!(
+            error(vec![1, 1, 3, 0]).to_string(),
+            "still running after 1000000 steps"
+        );
+        assert_eq!(
+            run_with_limit(&vec![3, 0], &state, false, 10),
+            Err(VmError::StepLimit { steps: 10 })
+        );
+
+        // bxl 7 is fine, only combo operands reserve 7
+        assert!(run(&vec![1, 7], &state, false).is_ok());
+    }
+
+    #[test]
+    fn large_shifts_clear_the_register() {
+        // adv B with B far past the width of a register
+        let state = State {
+            a: u64::MAX,
+            b: 1 << 40,
+            c: 0,
+        };
+        let (_, out_state) = run(&vec![0, 5], &state, false).unwrap();
+        assert_eq!(out_state.a, 0);
+
+        let state = State {
+            a: 1 << 63,
+            b: 63,
+            c: 0,
+        };
+        let (_, out_state) = run(&vec![7, 5], &state, false).unwrap();
+        assert_eq!(out_state.c, 1);
+    }
+}
@@ -0,0 +1,474 @@
+//! Finding values of register A which make a program print a given output.
+//!
+//! Puzzle programs are a single loop which prints something computed from A, shifts A right and
+//! jumps back to the start while A is non-zero. When a program has that shape, each loop
+//! iteration only depends on what is left of A, so A can be built up a digit at a time starting
+//! from the last output. [`QuineSolver::shape`] checks for that shape and works out which bits of A
+//! each output can see. Anything else falls back to trying every A up to a limit.
+
+use std::{fmt, ops::Range};
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::cancel::{CancelToken, Cancelled};
+
+use super::{
+    compiled::CompiledProgram,
+    disasm::{self, Decoded, Instruction},
+    ComboOperand, Op, State,
+};
+
+/// Steps a single candidate gets before we assume it never halts
+const CANDIDATE_STEP_LIMIT: u64 = 10_000;
+
+/// Why no value of A could be found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuineError {
+    /// The program isn't a simple shifting loop and brute force found nothing in `searched`
+    Unsupported {
+        reason: String,
+        searched: Range<u64>,
+    },
+    NoSolution,
+    Cancelled,
+}
+
+impl fmt::Display for QuineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuineError::Unsupported { reason, searched } => write!(
+                f,
+                "can't analyse the program ({reason}) and no A in {}..{} works",
+                searched.start, searched.end
+            ),
+            QuineError::NoSolution => write!(f, "no value of A produces the output"),
+            QuineError::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for QuineError {}
+
+impl From<Cancelled> for QuineError {
+    fn from(_: Cancelled) -> QuineError {
+        QuineError::Cancelled
+    }
+}
+
+/// What the analysis found out about a supported program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopShape {
+    /// How many bits A is shifted right by each time round the loop
+    pub shift: u32,
+    /// For each `out` in the loop, a mask of the bits of A (as it is at the top of the loop) which
+    /// can change what it prints
+    pub influence: Vec<u64>,
+}
+
+impl LoopShape {
+    /// How many bits from the bottom of A one iteration's output can depend on
+    pub fn window(&self) -> u32 {
+        let mask = self.influence.iter().fold(0, |acc, mask| acc | mask);
+        u64::BITS - mask.leading_zeros()
+    }
+
+    /// The bits of each new digit of A which some output sees. The rest are shifted away without
+    /// changing anything the program prints, so they can be anything.
+    pub fn digit_mask(&self) -> u64 {
+        let mask = self.influence.iter().fold(0, |acc, mask| acc | mask);
+        mask & ((1 << self.shift) - 1)
+    }
+}
+
+/// What one bit of a register is known to be, relative to A at the top of the loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bit {
+    Zero,
+    One,
+    /// Depends on these bits of A
+    Depends(u64),
+}
+
+impl Bit {
+    fn mask(self) -> u64 {
+        match self {
+            Bit::Depends(mask) => mask,
+            _ => 0,
+        }
+    }
+}
+
+type Value = [Bit; 64];
+
+fn literal(n: u64) -> Value {
+    std::array::from_fn(|i| if n >> i & 1 == 1 { Bit::One } else { Bit::Zero })
+}
+
+fn xor(lhs: &Value, rhs: &Value) -> Value {
+    std::array::from_fn(|i| match (lhs[i], rhs[i]) {
+        (Bit::Depends(a), b) | (b, Bit::Depends(a)) => Bit::Depends(a | b.mask()),
+        (a, b) if a == b => Bit::Zero,
+        _ => Bit::One,
+    })
+}
+
+fn low_bits(value: &Value) -> Value {
+    std::array::from_fn(|i| if i < 3 { value[i] } else { Bit::Zero })
+}
+
+/// `value >> by`, when `by` isn't known every shift it could be is taken into account
+fn shift_right(value: &Value, by: &Value) -> Value {
+    let bit = |i: usize| value.get(i).copied().unwrap_or(Bit::Zero);
+
+    if by.iter().all(|b| !matches!(b, Bit::Depends(_))) {
+        let by: u64 = (0..64).filter(|&i| by[i] == Bit::One).map(|i| 1 << i).sum();
+        let by = by.min(64) as usize;
+        return std::array::from_fn(|i| bit(i + by));
+    }
+
+    // The biggest the shift could be, capped since anything past 63 clears everything anyway
+    let max = by
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| **b != Bit::Zero)
+        .map(|(i, _)| i)
+        .max()
+        .map_or(0, |top| if top >= 6 { 63 } else { (2 << top) - 1 });
+    let by_mask = by.iter().fold(0, |acc, b| acc | b.mask());
+
+    std::array::from_fn(|i| {
+        let mask = (0..=max).fold(by_mask, |acc, s| acc | bit(i + s).mask());
+        Bit::Depends(mask)
+    })
+}
+
+/// Checks the program is one loop which shifts A by a fixed amount and works out which bits of A
+/// each output depends on
+fn analyze(program: &[u8]) -> Result<LoopShape, String> {
+    let disassembly = disasm::disassemble(program);
+    if let Some(Decoded::Invalid {
+        address, reason, ..
+    }) = disassembly
+        .lines
+        .iter()
+        .find(|line| matches!(line, Decoded::Invalid { .. }))
+    {
+        return Err(format!("{reason} at {address:02}"));
+    }
+
+    let instructions: Vec<Instruction> = disassembly.instructions().copied().collect();
+    match instructions.iter().position(|i| i.op == Op::Jnz) {
+        Some(position) if position + 1 == instructions.len() => {
+            if instructions[position].operand != 0 {
+                return Err("the loop doesn't start at the beginning".to_string());
+            }
+        }
+        Some(_) => return Err("jnz isn't the last instruction".to_string()),
+        None => return Err("there's no loop".to_string()),
+    }
+
+    let shifts: Vec<_> = instructions.iter().filter(|i| i.op == Op::Adv).collect();
+    let shift = match shifts[..] {
+        [adv] => match ComboOperand::decode(adv.operand) {
+            Some(ComboOperand::Literal(0)) => return Err("adv 0 never shrinks A".to_string()),
+            Some(ComboOperand::Literal(shift)) => shift as u32,
+            Some(_) => return Err("A is shifted by a register".to_string()),
+            None => return Err("A is shifted by a reserved operand".to_string()),
+        },
+        [] => return Err("A is never shifted".to_string()),
+        _ => return Err("A is shifted more than once".to_string()),
+    };
+
+    let mut a: Value = std::array::from_fn(|i| Bit::Depends(1 << i));
+    let mut b = None;
+    let mut c = None;
+    let mut influence = Vec::new();
+
+    let carried = |register| format!("{register} carries over between iterations");
+    for instruction in &instructions {
+        let combo = |b: &Option<Value>, c: &Option<Value>| match ComboOperand::decode(
+            instruction.operand,
+        ) {
+            Some(ComboOperand::Literal(x)) => Ok(literal(x as u64)),
+            Some(ComboOperand::RegisterA) => Ok(a),
+            Some(ComboOperand::RegisterB) => b.ok_or_else(|| carried("B")),
+            Some(ComboOperand::RegisterC) => c.ok_or_else(|| carried("C")),
+            None => Err("a combo operand is reserved".to_string()),
+        };
+
+        match instruction.op {
+            Op::Adv => a = shift_right(&a, &combo(&b, &c)?),
+            Op::Bxl => {
+                let value = b.ok_or_else(|| carried("B"))?;
+                b = Some(xor(&value, &literal(instruction.operand as u64)));
+            }
+            Op::Bst => b = Some(low_bits(&combo(&b, &c)?)),
+            Op::Jnz => {}
+            Op::Bxc => {
+                let lhs = b.ok_or_else(|| carried("B"))?;
+                let rhs = c.ok_or_else(|| carried("C"))?;
+                b = Some(xor(&lhs, &rhs));
+            }
+            Op::Out => {
+                let value = low_bits(&combo(&b, &c)?);
+                influence.push(value.iter().fold(0, |acc, bit| acc | bit.mask()));
+            }
+            Op::Bdv => b = Some(shift_right(&a, &combo(&b, &c)?)),
+            Op::Cdv => c = Some(shift_right(&a, &combo(&b, &c)?)),
+        }
+    }
+
+    if influence.is_empty() {
+        return Err("the loop never outputs anything".to_string());
+    }
+
+    Ok(LoopShape { shift, influence })
+}
+
+/// Searches for values of A which make `program` print a target output
+pub struct QuineSolver<'p> {
+    program: &'p [u8],
+    compiled: CompiledProgram,
+    state: State,
+    search_limit: u64,
+}
+
+impl<'p> QuineSolver<'p> {
+    pub fn new(program: &'p [u8], state: State) -> QuineSolver<'p> {
+        QuineSolver {
+            program,
+            compiled: CompiledProgram::new(program).with_step_limit(CANDIDATE_STEP_LIMIT),
+            state,
+            search_limit: 1 << 20,
+        }
+    }
+
+    /// How far to count when the program can't be analysed
+    pub fn with_search_limit(mut self, limit: u64) -> QuineSolver<'p> {
+        self.search_limit = limit;
+        self
+    }
+
+    /// The shape of the program's loop, or why it can't be worked backwards
+    pub fn shape(&self) -> Result<LoopShape, String> {
+        analyze(self.program)
+    }
+
+    /// The smallest A which makes the program print `target`
+    pub fn smallest(&self, target: &[u8], cancel: &CancelToken) -> Result<u64, QuineError> {
+        self.solve(target, false, cancel)?
+            .first()
+            .copied()
+            .ok_or(QuineError::NoSolution)
+    }
+
+    /// Every A which makes the program print `target`, smallest first. When the program can't
+    /// be analysed this is only the ones below the search limit.
+    pub fn all(&self, target: &[u8], cancel: &CancelToken) -> Result<Vec<u64>, QuineError> {
+        self.solve(target, true, cancel)
+    }
+
+    fn solve(
+        &self,
+        target: &[u8],
+        all: bool,
+        cancel: &CancelToken,
+    ) -> Result<Vec<u64>, QuineError> {
+        let shape = match analyze(self.program) {
+            Ok(shape) => shape,
+            Err(reason) => return self.search(target, all, reason, cancel),
+        };
+
+        let per_loop = shape.influence.len();
+        let mut found = Vec::new();
+        if !target.is_empty() && target.len().is_multiple_of(per_loop) {
+            self.build(&shape, target, 0, target.len(), all, &mut found, cancel)?;
+        }
+
+        Ok(found)
+    }
+
+    /// Tries every way of extending `seed` by one more iteration, so that the program prints
+    /// `target[start - per_loop..]`. Only the digit bits in [`LoopShape::digit_mask`] are run
+    /// through the program, but digits are still walked smallest first so solutions come out in
+    /// order.
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        &self,
+        shape: &LoopShape,
+        target: &[u8],
+        seed: u64,
+        start: usize,
+        all: bool,
+        found: &mut Vec<u64>,
+        cancel: &CancelToken,
+    ) -> Result<(), Cancelled> {
+        cancel.check()?;
+        let start = start - shape.influence.len();
+        let Some(shifted) = seed.checked_mul(1 << shape.shift) else {
+            return Ok(());
+        };
+
+        let seen = shape.digit_mask();
+        let mut works = vec![None; 1 << shape.shift];
+        for digit in 0..1 << shape.shift {
+            let candidate = shifted + digit;
+            let tested = (digit & seen) as usize;
+            if !*works[tested]
+                .get_or_insert_with(|| self.produces(shifted + tested as u64, &target[start..]))
+            {
+                continue;
+            }
+
+            if start == 0 {
+                found.push(candidate);
+            } else {
+                self.build(shape, target, candidate, start, all, found, cancel)?;
+            }
+            if !all && !found.is_empty() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Brute force for programs which can't be analysed
+    fn search(
+        &self,
+        target: &[u8],
+        all: bool,
+        reason: String,
+        cancel: &CancelToken,
+    ) -> Result<Vec<u64>, QuineError> {
+        let check = |a: u64| -> Result<bool, Cancelled> {
+            if a.is_multiple_of(4096) {
+                cancel.check()?;
+            }
+            Ok(self.produces(a, target))
+        };
+
+        let found: Vec<u64> = if all {
+            (0..self.search_limit)
+                .into_par_iter()
+                .filter_map(|a| check(a).map(|works| works.then_some(a)).transpose())
+                .collect::<Result<_, Cancelled>>()?
+        } else {
+            (0..self.search_limit)
+                .into_par_iter()
+                .find_map_first(|a| check(a).map(|works| works.then_some(a)).transpose())
+                .transpose()?
+                .into_iter()
+                .collect()
+        };
+
+        if found.is_empty() {
+            return Err(QuineError::Unsupported {
+                reason,
+                searched: 0..self.search_limit,
+            });
+        }
+
+        Ok(found)
+    }
+
+    /// Whether starting with `a` prints exactly `target`, giving up as soon as it can't
+    fn produces(&self, a: u64, target: &[u8]) -> bool {
+        self.compiled
+            .produces(&State { a, ..self.state }, target)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{asm::assemble, parse_input, run};
+    use super::*;
+
+    const REAL: [u8; 16] = [2, 4, 1, 1, 7, 5, 4, 7, 1, 4, 0, 3, 5, 5, 3, 0];
+
+    fn zero() -> State {
+        State { a: 0, b: 0, c: 0 }
+    }
+
+    #[test]
+    fn analyzes_loops() {
+        let shape = analyze(&REAL).unwrap();
+        assert_eq!(shape.shift, 3);
+        // B = A % 8 picks how far C shifts, so the output sees up to 7 bits past the bottom 3
+        assert_eq!(shape.influence, vec![0b11_1111_1111]);
+        assert_eq!(shape.window(), 10);
+        assert_eq!(shape.digit_mask(), 0b111);
+
+        let example = analyze(&[0, 3, 5, 4, 3, 0]).unwrap();
+        assert_eq!(example.influence, vec![0b111 << 3]);
+    }
+
+    #[test]
+    fn rejects_unsupported_shapes() {
+        let reason = |source| analyze(&assemble(source).unwrap()).unwrap_err();
+
+        assert_eq!(reason("out A"), "there's no loop");
+        assert_eq!(
+            reason("adv 1\nadv 2\nout A\njnz 0"),
+            "A is shifted more than once"
+        );
+        assert_eq!(
+            reason("bst A\nadv B\nout A\njnz 0"),
+            "A is shifted by a register"
+        );
+        assert_eq!(
+            reason("adv 1\nbxl 1\nout B\njnz 0"),
+            "B carries over between iterations"
+        );
+        assert_eq!(reason("adv 3\njnz 0"), "the loop never outputs anything");
+    }
+
+    #[test]
+    fn finds_quines() {
+        let (state, program) =
+            parse_input("Register A: 2024\nRegister B: 0\nRegister C: 0\n\nProgram: 0,3,5,4,3,0")
+                .unwrap();
+        let solver = QuineSolver::new(&program, state.clone());
+
+        assert_eq!(solver.smallest(&program, &CancelToken::new()), Ok(117440));
+
+        let solver = QuineSolver::new(&REAL, zero());
+        let a = solver.smallest(&REAL, &CancelToken::new()).unwrap();
+        let (output, _) = run(&REAL.to_vec(), &State { a, ..zero() }, false).unwrap();
+        assert_eq!(output, REAL);
+    }
+
+    #[test]
+    fn finds_every_solution_in_order() {
+        // Bit 0 is shifted away without ever being printed, so it can be anything
+        let program = assemble("bdv 1\nout B\nadv 3\njnz 0").unwrap();
+        let solver = QuineSolver::new(&program, zero());
+        assert_eq!(solver.shape().unwrap().digit_mask(), 0b110);
+
+        let solutions = solver.all(&[1, 2], &CancelToken::new()).unwrap();
+        assert_eq!(solutions, vec![34, 35]);
+        for a in solutions {
+            let (output, _) = run(&program, &State { a, ..zero() }, false).unwrap();
+            assert_eq!(output, vec![1, 2]);
+        }
+
+        assert_eq!(solver.all(&[5, 5], &CancelToken::new()), Ok(vec![]));
+    }
+
+    #[test]
+    fn falls_back_to_searching() {
+        // Shifting by a register can't be worked backwards
+        let program = assemble("bst A\nbxl 1\nadv B\nout A\njnz 0").unwrap();
+        let solver = QuineSolver::new(&program, zero()).with_search_limit(1 << 12);
+
+        assert_eq!(solver.smallest(&[2, 3, 0], &CancelToken::new()), Ok(107));
+        assert_eq!(
+            solver.smallest(&[7, 7, 7, 7, 7], &CancelToken::new()),
+            Err(QuineError::Unsupported {
+                reason: "A is shifted by a register".to_string(),
+                searched: 0..4096,
+            })
+        );
+    }
+}
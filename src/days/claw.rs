@@ -1,40 +1,48 @@
-use crate::solution::Solution;
-
-#[derive(Debug, Clone)]
-struct ClawMachine {
-    ax: i64,
-    ay: i64,
-    bx: i64,
-    by: i64,
-    px: i64,
-    py: i64,
+//! Day 13: Claw Contraption
+
+use std::str::FromStr;
+
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError, ParseResult, Section},
+    solution::Solution,
+};
+
+/// A claw machine: how far buttons A and B move the claw and where the prize is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClawMachine {
+    pub ax: i64,
+    pub ay: i64,
+    pub bx: i64,
+    pub by: i64,
+    pub px: i64,
+    pub py: i64,
 }
 
 impl ClawMachine {
-    fn new(input: &str) -> ClawMachine {
-        let lines: Vec<_> = input.trim().lines().collect();
-        let (ba1, ba2) = lines[0].split_once(',').unwrap();
-        let (bb1, bb2) = lines[1].split_once(',').unwrap();
-        let (p1, p2) = lines[2].split_once(',').unwrap();
-
-        ClawMachine {
-            ax: ba1[12..].parse().unwrap(),
-            ay: ba2[3..].parse().unwrap(),
-            bx: bb1[12..].parse().unwrap(),
-            by: bb2[3..].parse().unwrap(),
-            px: p1[9..].parse().unwrap(),
-            py: p2[3..].parse().unwrap(),
-        }
+    fn parse(section: &Section) -> ParseResult<ClawMachine> {
+        let [ax, ay, bx, by, px, py] = section.ints_n()?;
+
+        Ok(ClawMachine {
+            ax,
+            ay,
+            bx,
+            by,
+            px,
+            py,
+        })
     }
 
-    fn scaled(&self) -> ClawMachine {
+    /// The same machine with the prize moved out by the unit conversion error
+    pub fn scaled(&self) -> ClawMachine {
         let mut m = self.clone();
         m.px += 10000000000000;
         m.py += 10000000000000;
         m
     }
 
-    fn solve(&self) -> Option<(i64, i64)> {
+    /// Number of (A, B) presses which land exactly on the prize, if there is a whole solution
+    pub fn solve(&self) -> Option<(i64, i64)> {
         // Return early if the determinent DNE
         let d_inv = self.ax * self.by - self.ay * self.bx;
         if d_inv == 0 {
@@ -52,26 +60,85 @@ impl ClawMachine {
     }
 }
 
+impl FromStr for ClawMachine {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ClawMachine::parse(&Section::new(s))
+    }
+}
+
+/// Parses every blank-line-separated machine in the input
+pub fn parse_input(puzzle_input: &str) -> ParseResult<Vec<ClawMachine>> {
+    parse::sections(puzzle_input)
+        .iter()
+        .map(ClawMachine::parse)
+        .collect()
+}
+
 pub struct ClawContraption;
 
 impl Solution for ClawContraption {
-    fn part1(puzzle_input: String) -> String {
-        let machines: Vec<_> = puzzle_input.split("\n\n").map(ClawMachine::new).collect();
+    fn part1(puzzle_input: String) -> Answer {
+        let machines = parse_input(&puzzle_input).unwrap();
         machines
             .iter()
             .filter_map(ClawMachine::solve)
             .map(|(a, b)| a * 3 + b)
             .sum::<i64>()
-            .to_string()
+            .into()
     }
-    fn part2(puzzle_input: String) -> String {
-        let machines: Vec<_> = puzzle_input.split("\n\n").map(ClawMachine::new).collect();
+    fn part2(puzzle_input: String) -> Answer {
+        let machines = parse_input(&puzzle_input).unwrap();
         machines
             .iter()
             .map(ClawMachine::scaled)
             .filter_map(|m| m.solve())
             .map(|(a, b)| a * 3 + b)
             .sum::<i64>()
-            .to_string()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_machines() {
+        let machine: ClawMachine = "Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            machine,
+            ClawMachine {
+                ax: 94,
+                ay: 34,
+                bx: 22,
+                by: 67,
+                px: 8400,
+                py: 5400
+            }
+        );
+        assert_eq!(machine.solve(), Some((80, 40)));
+
+        let err = parse_input("Button A: X+94, Y+34\nPrize: X=8400, Y=5400\n\nButton A: X+1")
+            .unwrap_err();
+        assert_eq!(err.line, 1);
+    }
+
+    #[test]
+    fn unsolvable_machine() {
+        let machine: ClawMachine = "Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176"
+            .parse()
+            .unwrap();
+
+        assert_eq!(machine.solve(), None);
+        assert!(machine.scaled().solve().is_some());
     }
 }
@@ -1,57 +1,106 @@
-use std::collections::HashMap;
+//! Day 1: Historian Hysteria
 
-use crate::solution::Solution;
+use std::{collections::HashMap, str::FromStr};
 
-pub struct ListSimilarity;
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError},
+    solution::Solution,
+};
 
-impl ListSimilarity {
-    fn parse_input(input: String) -> (Vec<i32>, Vec<i32>) {
-        input
-            .lines()
-            .map(|l| {
-                let a: Vec<_> = l
-                    .split("   ")
-                    .map(|v| v.parse::<i32>().unwrap())
-                    .take(2)
-                    .collect();
-                (a[0], a[1])
-            })
-            .unzip()
-    }
+/// The two location id lists written side by side in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationLists {
+    pub left: Vec<i32>,
+    pub right: Vec<i32>,
 }
 
-impl Solution for ListSimilarity {
-    fn part1(puzzle_input: String) -> String {
-        let (mut list1, mut list2) = ListSimilarity::parse_input(puzzle_input);
+impl LocationLists {
+    /// Sum of the distances between the lists when both are sorted
+    pub fn total_distance(&self) -> i32 {
+        let mut list1 = self.left.clone();
+        let mut list2 = self.right.clone();
 
         list1.sort();
         list2.sort();
 
-        let diff = list1
+        list1
             .iter()
             .zip(list2)
             .map(|(a, b)| (a - b).abs())
             .sum::<i32>()
-            .to_string();
-
-        diff
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let (list1, list2) = ListSimilarity::parse_input(puzzle_input);
-
+    /// Sum of each left number multiplied by how often it appears in the right list
+    pub fn similarity_score(&self) -> i32 {
         // Build a hash map of counts in list 2
         let mut counts: HashMap<i32, i32> = HashMap::new();
-        for num in list2 {
-            let current = counts.get(&num).unwrap_or(&0);
-            counts.insert(num, current + 1);
+        for num in &self.right {
+            let current = counts.get(num).unwrap_or(&0);
+            counts.insert(*num, current + 1);
         }
 
-
-        list1
+        self.left
             .iter()
-            .map(|n| n * counts.get(&n).unwrap_or(&0) )
+            .map(|n| n * counts.get(n).unwrap_or(&0))
             .sum::<i32>()
-            .to_string()
+    }
+}
+
+impl FromStr for LocationLists {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (left, right) = parse::lines(s)
+            .map(|line| line.ints_n::<i32, 2>().map(|[a, b]| (a, b)))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+
+        Ok(LocationLists { left, right })
+    }
+}
+
+pub struct ListSimilarity;
+
+impl Solution for ListSimilarity {
+    fn part1(puzzle_input: String) -> Answer {
+        let lists: LocationLists = puzzle_input.parse().unwrap();
+
+        lists.total_distance().into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let lists: LocationLists = puzzle_input.parse().unwrap();
+
+        lists.similarity_score().into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "3   4
+4   3
+2   5
+1   3
+3   9
+3   3";
+
+    #[test]
+    fn parses_lists() {
+        let lists: LocationLists = EXAMPLE.parse().unwrap();
+        assert_eq!(lists.left, vec![3, 4, 2, 1, 3, 3]);
+        assert_eq!(lists.right, vec![4, 3, 5, 3, 9, 3]);
+
+        assert_eq!("1   2\n3".parse::<LocationLists>().unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn example() {
+        let lists: LocationLists = EXAMPLE.parse().unwrap();
+        assert_eq!(lists.total_distance(), 11);
+        assert_eq!(lists.similarity_score(), 31);
     }
 }
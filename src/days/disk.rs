@@ -1,35 +1,55 @@
-use crate::solution::Solution;
+//! Day 9: Disk Fragmenter
+
+use std::str::FromStr;
+
+use crate::{answer::Answer, parse::ParseError, solution::Solution};
 
 #[derive(Debug, Clone)]
-struct Occupied {
-    size: usize,
-    file_id: u64,
+pub struct Occupied {
+    pub size: usize,
+    pub file_id: u64,
 }
 
 #[derive(Debug, Clone)]
-struct Free {
-    size: usize,
+pub struct Free {
+    pub size: usize,
 }
 
 #[derive(Debug, Clone)]
-enum DiskSection {
+pub enum DiskSection {
     Occupied(Occupied),
     Free(Free),
 }
 
-fn parse_input(puzzle_input: String) -> Vec<usize> {
-    puzzle_input
-        .trim()
-        .chars()
-        .map(|c| c.to_string().parse().unwrap())
-        .collect()
+/// The dense disk map, alternating file and free space lengths
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskMap {
+    pub sizes: Vec<usize>,
 }
 
-pub struct DiskFragmenter;
+impl FromStr for DiskMap {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sizes = s
+            .trim()
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                c.to_digit(10).map(|d| d as usize).ok_or_else(|| {
+                    ParseError::new(1, format!("unexpected character '{c}' in column {}", i + 1))
+                })
+            })
+            .collect::<Result<_, _>>()?;
 
-impl Solution for DiskFragmenter {
-    fn part1(puzzle_input: String) -> String {
-        let nums = parse_input(puzzle_input);
+        Ok(DiskMap { sizes })
+    }
+}
+
+impl DiskMap {
+    /// Checksum after moving single blocks from the end into the leftmost free space
+    pub fn compact_blocks_checksum(&self) -> usize {
+        let nums = &self.sizes;
 
         // Build an array of the already compacted file blocks
         let mut blocks = Vec::new();
@@ -59,11 +79,12 @@ impl Solution for DiskFragmenter {
             }
         }
 
-        checksum.to_string()
+        checksum
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let nums = parse_input(puzzle_input);
+    /// Checksum after moving whole files, highest id first, into the leftmost gap that fits
+    pub fn compact_files_checksum(&self) -> u64 {
+        let nums = &self.sizes;
 
         // Build sections list
         let mut sections = Vec::new();
@@ -111,7 +132,7 @@ impl Solution for DiskFragmenter {
 
             if let Some((slot_idx, slot)) = slot_tuple {
                 if slot_idx > section_idx {
-                    continue
+                    continue;
                 }
                 // Remove the file section and empty slot and add it in correctly
                 let section = section.clone();
@@ -119,7 +140,10 @@ impl Solution for DiskFragmenter {
 
                 // First swap out the file for an empty section. We don't need to compact
                 // these since we are always moving right to left
-                let _ = std::mem::replace(&mut sections[section_idx], DiskSection::Free(Free { size: section.size }));
+                let _ = std::mem::replace(
+                    &mut sections[section_idx],
+                    DiskSection::Free(Free { size: section.size }),
+                );
 
                 if slot.size > section.size {
                     let new_sections = vec![
@@ -132,26 +156,72 @@ impl Solution for DiskFragmenter {
                         }),
                     ];
 
-                    sections = [&sections[0..slot_idx], &new_sections, &sections[slot_idx + 1..]].concat();
+                    sections = [
+                        &sections[0..slot_idx],
+                        &new_sections,
+                        &sections[slot_idx + 1..],
+                    ]
+                    .concat();
                 } else {
-                    let _ = std::mem::replace(&mut sections[slot_idx], DiskSection::Occupied(Occupied {
-                        size: section.size,
-                        file_id: section.file_id,
-                    }));
+                    let _ = std::mem::replace(
+                        &mut sections[slot_idx],
+                        DiskSection::Occupied(Occupied {
+                            size: section.size,
+                            file_id: section.file_id,
+                        }),
+                    );
                 };
             }
         }
 
-        
-        sections.iter().fold((0, 0), |(block_idx, checksum), section| match section {
-            DiskSection::Free(free) => (block_idx + free.size, checksum),
-            DiskSection::Occupied(occupied) => {
-                let mut c = checksum;
-                for idx in block_idx..block_idx+occupied.size {
-                    c += (idx as u64) * occupied.file_id;
+        sections
+            .iter()
+            .fold((0, 0), |(block_idx, checksum), section| match section {
+                DiskSection::Free(free) => (block_idx + free.size, checksum),
+                DiskSection::Occupied(occupied) => {
+                    let mut c = checksum;
+                    for idx in block_idx..block_idx + occupied.size {
+                        c += (idx as u64) * occupied.file_id;
+                    }
+                    (block_idx + occupied.size, c)
                 }
-                (block_idx + occupied.size, c)
-            },
-        }).1.to_string()
+            })
+            .1
+    }
+}
+
+pub struct DiskFragmenter;
+
+impl Solution for DiskFragmenter {
+    fn part1(puzzle_input: String) -> Answer {
+        let disk: DiskMap = puzzle_input.parse().unwrap();
+
+        disk.compact_blocks_checksum().into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let disk: DiskMap = puzzle_input.parse().unwrap();
+
+        disk.compact_files_checksum().into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_disk_map() {
+        let disk: DiskMap = "12345\n".parse().unwrap();
+        assert_eq!(disk.sizes, vec![1, 2, 3, 4, 5]);
+
+        assert!("12a45".parse::<DiskMap>().is_err());
+    }
+
+    #[test]
+    fn example() {
+        let disk: DiskMap = "2333133121414131402".parse().unwrap();
+        assert_eq!(disk.compact_blocks_checksum(), 1928);
+        assert_eq!(disk.compact_files_checksum(), 2858);
     }
 }
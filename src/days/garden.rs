@@ -1,21 +1,30 @@
+//! Day 12: Garden Groups
+
 use std::{
     collections::{HashMap, HashSet},
     ops::Add,
+    str::FromStr,
 };
 
-use crate::solution::Solution;
+use crate::{
+    answer::Answer,
+    parse::{ParseError, Section},
+    solution::Solution,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Point(i32, i32);
+pub struct Point(pub i32, pub i32);
 
+/// A straight piece of fence between grid corners
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct Edge {
-    start: Point,
-    end: Point,
+pub struct Edge {
+    pub start: Point,
+    pub end: Point,
 }
 
 impl Edge {
-    fn new(point: Point, dir: Direction) -> Edge {
+    /// The unit fence on the `dir` side of the plot at `point`
+    pub fn new(point: Point, dir: Direction) -> Edge {
         match dir {
             Direction::Up => Edge {
                 start: point,
@@ -36,7 +45,7 @@ impl Edge {
         }
     }
 
-    fn merge(a: Edge, b: Edge) -> Edge {
+    pub fn merge(a: Edge, b: Edge) -> Edge {
         if b.end == a.start {
             Edge {
                 start: b.start,
@@ -52,13 +61,13 @@ impl Edge {
         }
     }
 
-    fn is_horizontal(&self) -> bool {
+    pub fn is_horizontal(&self) -> bool {
         assert!(self.start != self.end);
-        return self.start.1 == self.end.1;
+        self.start.1 == self.end.1
     }
 
     // Returns a vector of edges that could extend this edge, respected all_edges
-    fn possible_extensions(&self, all_edges: &HashSet<Edge>) -> Vec<Edge> {
+    pub fn possible_extensions(&self, all_edges: &HashSet<Edge>) -> Vec<Edge> {
         let mut extensions = Vec::new();
         match self.is_horizontal() {
             true => {
@@ -125,7 +134,7 @@ impl Add for Point {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
-enum Direction {
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -133,7 +142,7 @@ enum Direction {
 }
 
 impl Direction {
-    fn as_point(&self) -> Point {
+    pub fn as_point(&self) -> Point {
         match self {
             Direction::Up => Point(0, -1),
             Direction::Down => Point(0, 1),
@@ -142,7 +151,7 @@ impl Direction {
         }
     }
 
-    fn all_dirs() -> Vec<Direction> {
+    pub fn all_dirs() -> Vec<Direction> {
         vec![
             Direction::Up,
             Direction::Down,
@@ -152,18 +161,52 @@ impl Direction {
     }
 }
 
-fn parse_input(puzzle_input: String) -> HashMap<Point, char> {
-    let mut hm = HashMap::new();
-    for (y, row) in puzzle_input.lines().enumerate() {
-        for (x, cell) in row.chars().enumerate() {
-            hm.insert(Point(x as i32, y as i32), cell);
+/// The garden plots, each labelled with its plant type
+#[derive(Debug, Clone)]
+pub struct Garden {
+    pub plots: HashMap<Point, char>,
+}
+
+impl Garden {
+    /// Total fencing cost, area times perimeter per region, or area times number of sides when
+    /// the bulk discount applies
+    pub fn fence_price(&self, bulk_discount: bool) -> u64 {
+        let mut to_visit: HashSet<_> = self.plots.keys().cloned().collect();
+        let mut result = 0;
+        while let Some(point) = to_visit.iter().next().cloned() {
+            let (area, perimeter, edges) =
+                get_area_and_perimeter_and_edges(&self.plots, point, &mut to_visit);
+
+            result += if bulk_discount {
+                area * count_sides(&edges)
+            } else {
+                area * perimeter
+            };
         }
+
+        result
     }
+}
+
+impl FromStr for Garden {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let grid = Section::new(s).grid(Some)?;
+
+        let mut plots = HashMap::new();
+        for (y, row) in grid.into_iter().enumerate() {
+            for (x, cell) in row.into_iter().enumerate() {
+                plots.insert(Point(x as i32, y as i32), cell);
+            }
+        }
 
-    hm
+        Ok(Garden { plots })
+    }
 }
 
-fn get_area_and_perimeter_and_edges(
+/// Flood fills the region containing `start`, removing its plots from `to_visit`
+pub fn get_area_and_perimeter_and_edges(
     map: &HashMap<Point, char>,
     start: Point,
     to_visit: &mut HashSet<Point>,
@@ -207,12 +250,13 @@ fn get_area_and_perimeter_and_edges(
     (area.len() as u64, perimeter, edges)
 }
 
-fn count_sides(all_edges: &HashSet<Edge>) -> u64 {
+/// Merges unit fence edges into straight sides and counts them
+pub fn count_sides(all_edges: &HashSet<Edge>) -> u64 {
     let mut edges_to_merge = all_edges.clone();
     let mut merged_edges = HashSet::new();
 
     // While we have unused edges
-    while let Some(edge) = edges_to_merge.iter().cloned().next() {
+    while let Some(edge) = edges_to_merge.iter().next().cloned() {
         // Take a primitive edge
         let mut current_edge = edges_to_merge.take(&edge).unwrap();
 
@@ -244,38 +288,32 @@ fn count_sides(all_edges: &HashSet<Edge>) -> u64 {
 pub struct GardenGroupSolution;
 
 impl Solution for GardenGroupSolution {
-    fn part1(puzzle_input: String) -> String {
-        let map = parse_input(puzzle_input);
+    fn part1(puzzle_input: String) -> Answer {
+        let garden: Garden = puzzle_input.parse().unwrap();
 
-        let mut to_visit: HashSet<_> = map.keys().cloned().collect();
-        let mut result = 0;
-        while let Some(point) = to_visit.iter().next().cloned() {
-            let (area, perimeter, _) = get_area_and_perimeter_and_edges(&map, point, &mut to_visit);
-            result += area * perimeter
-        }
-
-        result.to_string()
+        garden.fence_price(false).into()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let map = parse_input(puzzle_input);
-
-        let mut to_visit: HashSet<_> = map.keys().cloned().collect();
-        let mut result = 0;
-        while let Some(point) = to_visit.iter().next().cloned() {
-            let (area, _, edges) = get_area_and_perimeter_and_edges(&map, point, &mut to_visit);
-            let num_sides = count_sides(&edges);
-
-            result += area * num_sides
-        }
+    fn part2(puzzle_input: String) -> Answer {
+        let garden: Garden = puzzle_input.parse().unwrap();
 
-        result.to_string()
+        garden.fence_price(true).into()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{days::garden::GardenGroupSolution, solution::Solution};
+    use super::*;
+
+    #[test]
+    fn parses_garden() {
+        let garden: Garden = "AAAA\nBBCD".parse().unwrap();
+        assert_eq!(garden.plots.len(), 8);
+        assert_eq!(garden.plots.get(&Point(3, 1)), Some(&'D'));
+        assert_eq!(garden.fence_price(false), 4 * 10 + 2 * 6 + 4 + 4);
+
+        assert_eq!("AAAA\nBBC".parse::<Garden>().unwrap_err().line, 2);
+    }
 
     #[test]
     fn case1() {
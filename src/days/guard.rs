@@ -1,13 +1,20 @@
-use core::panic;
+//! Day 6: Guard Gallivant
+
 use std::{
     collections::{HashMap, HashSet},
     ops::Add,
+    str::FromStr,
 };
 
-use crate::solution::Solution;
+use crate::{
+    answer::Answer,
+    cancel::{CancelToken, Cancelled},
+    parse::{ParseError, Section},
+    solution::Solution,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Point(i32, i32);
+pub struct Point(pub i32, pub i32);
 
 impl Add for Point {
     type Output = Point;
@@ -16,14 +23,14 @@ impl Add for Point {
     }
 }
 
-#[derive(Debug, Clone)]
-enum Map {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Map {
     Wall,
     Space,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
-enum Direction {
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -31,7 +38,7 @@ enum Direction {
 }
 
 impl Direction {
-    fn as_point(&self) -> Point {
+    pub fn as_point(&self) -> Point {
         match self {
             Direction::Up => Point(0, -1),
             Direction::Down => Point(0, 1),
@@ -40,7 +47,8 @@ impl Direction {
         }
     }
 
-    fn rotate(&self) -> Direction {
+    /// Turns 90 degrees to the right
+    pub fn rotate(&self) -> Direction {
         match self {
             Direction::Up => Direction::Right,
             Direction::Down => Direction::Left,
@@ -50,42 +58,62 @@ impl Direction {
     }
 }
 
-fn parse_input(puzzle_input: String) -> (HashMap<Point, Map>, Point) {
-    let mut hm = HashMap::new();
-    let mut pos = None;
-    for (y, row) in puzzle_input.lines().enumerate() {
-        for (x, cell) in row.chars().enumerate() {
-            hm.insert(
-                Point(x as i32, y as i32),
-                match cell {
-                    '#' => Map::Wall,
-                    '.' => Map::Space,
-                    '^' => {
-                        pos = Some(Point(x as i32, y as i32));
-                        Map::Space
-                    }
-                    x => panic!("Unknown map element {x}"),
-                },
-            );
+/// The lab map and where the guard starts, facing up
+#[derive(Debug, Clone)]
+pub struct Lab {
+    pub map: HashMap<Point, Map>,
+    pub start: Point,
+}
+
+impl Lab {
+    /// Every position the guard visits before walking off the map
+    pub fn patrol(&self) -> HashSet<Point> {
+        let mut visited = HashSet::new();
+        let mut current_state = (self.start, Direction::Up);
+        loop {
+            let (pos, dir) = current_state;
+            visited.insert(pos);
+
+            // Advance state
+            current_state = match self.map.get(&(pos + dir.as_point())) {
+                Some(Map::Wall) => (pos, dir.rotate()),
+                Some(Map::Space) => (pos + dir.as_point(), dir),
+                None => break,
+            }
         }
+
+        visited
     }
 
-    (hm, pos.expect("Map should have starting point"))
-}
+    /// Number of places a single new wall would trap the guard in a loop. Each candidate
+    /// simulates the whole patrol, so this checks `cancel` between candidates.
+    pub fn count_loop_obstructions(&self, cancel: &CancelToken) -> Result<usize, Cancelled> {
+        // Collected the candidate locations for the obstacle
+        let mut visited = self.patrol();
 
-pub struct GuardSolution;
+        // Remove the non-allowed positions
+        visited.remove(&(self.start + Direction::Up.as_point()));
+        visited.remove(&(self.start));
+
+        let mut count = 0;
+        for candidate in &visited {
+            cancel.check()?;
+            if self.loops_with_obstruction(candidate) {
+                count += 1;
+            }
+        }
 
-impl GuardSolution {
-    fn add_obstruction_and_check_for_cycle(
-        obstruction: &Point,
-        mut map: HashMap<Point, Map>,
-        state: (Point, Direction),
-    ) -> bool {
+        Ok(count)
+    }
+
+    /// Whether adding a wall at `obstruction` traps the guard in a loop
+    pub fn loops_with_obstruction(&self, obstruction: &Point) -> bool {
         let mut visited = HashSet::new();
-        let mut current_state = state;
+        let mut current_state = (self.start, Direction::Up);
 
         // Add the new obstruction
-        map.insert(obstruction.clone(), Map::Wall);
+        let mut map = self.map.clone();
+        map.insert(*obstruction, Map::Wall);
 
         loop {
             let (pos, dir) = current_state;
@@ -105,58 +133,97 @@ impl GuardSolution {
     }
 }
 
-impl Solution for GuardSolution {
-    fn part1(puzzle_input: String) -> String {
-        let (map, start_pos) = parse_input(puzzle_input);
-
-        let mut visited = HashSet::new();
-        let mut current_state = (start_pos, Direction::Up);
-        loop {
-            let (pos, dir) = current_state;
-            visited.insert(pos);
-            current_state = match map.get(&(pos + dir.as_point())) {
-                Some(Map::Wall) => (pos, dir.rotate()),
-                Some(Map::Space) => (pos + dir.as_point(), dir),
-                None => break,
+impl FromStr for Lab {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let section = Section::new(s);
+        let grid = section.grid(|cell| match cell {
+            '#' => Some((Map::Wall, false)),
+            '.' => Some((Map::Space, false)),
+            '^' => Some((Map::Space, true)),
+            _ => None,
+        })?;
+
+        let mut map = HashMap::new();
+        let mut start = None;
+        for (y, row) in grid.into_iter().enumerate() {
+            for (x, (tile, is_start)) in row.into_iter().enumerate() {
+                let pos = Point(x as i32, y as i32);
+                if is_start {
+                    start = Some(pos);
+                }
+                map.insert(pos, tile);
             }
         }
 
-        visited.len().to_string()
+        Ok(Lab {
+            map,
+            start: start.ok_or_else(|| section.error("map should have a starting point"))?,
+        })
     }
+}
 
-    fn part2(puzzle_input: String) -> String {
-        let (map, start_pos) = parse_input(puzzle_input);
+pub struct GuardSolution;
 
-        // Collected the candidate locations for the obstacle
-        let mut visited = HashSet::new();
-        let mut current_state = (start_pos, Direction::Up);
-        loop {
-            let (pos, dir) = current_state;
-            visited.insert(pos);
+impl Solution for GuardSolution {
+    fn part1(puzzle_input: String) -> Answer {
+        let lab: Lab = puzzle_input.parse().unwrap();
 
-            // Advance state
-            current_state = match map.get(&(pos + dir.as_point())) {
-                Some(Map::Wall) => (pos, dir.rotate()),
-                Some(Map::Space) => (pos + dir.as_point(), dir),
-                None => break,
-            }
-        }
+        lab.patrol().len().into()
+    }
 
-        // Remove the non-allowed positions
-        visited.remove(&(start_pos + Direction::Up.as_point()));
-        visited.remove(&(start_pos));
+    fn part2(puzzle_input: String) -> Answer {
+        GuardSolution::part2_cancellable(puzzle_input, &CancelToken::new()).unwrap()
+    }
 
-        visited
-            .iter()
-            .filter_map(|v| {
-                GuardSolution::add_obstruction_and_check_for_cycle(
-                    v,
-                    map.clone(),
-                    (start_pos, Direction::Up),
-                )
-                .then_some(1)
-            })
-            .count()
-            .to_string()
+    fn part2_cancellable(puzzle_input: String, cancel: &CancelToken) -> Result<Answer, Cancelled> {
+        let lab: Lab = puzzle_input.parse().unwrap();
+
+        Ok(lab.count_loop_obstructions(cancel)?.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn parses_lab() {
+        let lab: Lab = EXAMPLE.parse().unwrap();
+        assert_eq!(lab.start, Point(4, 6));
+        assert_eq!(lab.map.get(&Point(4, 0)), Some(&Map::Wall));
+
+        assert_eq!("..\n.?".parse::<Lab>().unwrap_err().line, 2);
+        assert!("..\n..".parse::<Lab>().is_err());
+    }
+
+    #[test]
+    fn example() {
+        let lab: Lab = EXAMPLE.parse().unwrap();
+        assert_eq!(lab.patrol().len(), 41);
+        assert!(lab.loops_with_obstruction(&Point(3, 6)));
+        assert!(!lab.loops_with_obstruction(&Point(5, 6)));
+    }
+
+    #[test]
+    fn obstruction_search_can_be_cancelled() {
+        let lab: Lab = EXAMPLE.parse().unwrap();
+        assert_eq!(lab.count_loop_obstructions(&CancelToken::new()), Ok(6));
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert_eq!(lab.count_loop_obstructions(&cancel), Err(Cancelled));
     }
 }
@@ -1,13 +1,20 @@
+//! Day 10: Hoof It
+
 use std::{
     collections::{HashMap, HashSet},
     ops::Add,
+    str::FromStr,
     vec,
 };
 
-use crate::solution::Solution;
+use crate::{
+    answer::Answer,
+    parse::{ParseError, Section},
+    solution::Solution,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Point(i32, i32);
+pub struct Point(pub i32, pub i32);
 
 impl Add for Point {
     type Output = Point;
@@ -17,7 +24,7 @@ impl Add for Point {
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Direction {
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -25,7 +32,7 @@ enum Direction {
 }
 
 impl Direction {
-    fn as_point(&self) -> Point {
+    pub fn as_point(&self) -> Point {
         match self {
             Direction::Up => Point(0, -1),
             Direction::Down => Point(0, 1),
@@ -33,7 +40,7 @@ impl Direction {
             Direction::Right => Point(1, 0),
         }
     }
-    fn all_dirs() -> Vec<Direction> {
+    pub fn all_dirs() -> Vec<Direction> {
         vec![
             Direction::Up,
             Direction::Down,
@@ -43,17 +50,54 @@ impl Direction {
     }
 }
 
-fn parse_input(puzzle_input: String) -> HashMap<Point, u32> {
-    let mut hm = HashMap::new();
-    for (y, row) in puzzle_input.lines().enumerate() {
-        for (x, cell) in row.chars().enumerate() {
-            hm.insert(Point(x as i32, y as i32), cell.to_string().parse().unwrap());
+/// Heights of the topographic map, 0 to 9
+#[derive(Debug, Clone)]
+pub struct TrailMap {
+    pub heights: HashMap<Point, u32>,
+}
+
+impl TrailMap {
+    pub fn trailheads(&self) -> impl Iterator<Item = &Point> {
+        self.heights.iter().filter_map(|(p, v)| match v {
+            0 => Some(p),
+            _ => None,
+        })
+    }
+
+    /// Sum of every trailhead's score, see [`count_trails`]
+    pub fn total_score(&self) -> u32 {
+        self.trailheads()
+            .map(|t| count_trails(&self.heights, t))
+            .sum()
+    }
+
+    /// Sum of every trailhead's rating, see [`rate_trailhead`]
+    pub fn total_rating(&self) -> u32 {
+        self.trailheads()
+            .map(|t| rate_trailhead(&self.heights, t, 0))
+            .sum()
+    }
+}
+
+impl FromStr for TrailMap {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let grid = Section::new(s).grid(|cell| cell.to_digit(10))?;
+
+        let mut heights = HashMap::new();
+        for (y, row) in grid.into_iter().enumerate() {
+            for (x, height) in row.into_iter().enumerate() {
+                heights.insert(Point(x as i32, y as i32), height);
+            }
         }
+
+        Ok(TrailMap { heights })
     }
-    hm
 }
 
-fn count_trails(map: &HashMap<Point, u32>, trailhead: &Point) -> u32 {
+/// Number of distinct summits reachable from `trailhead`
+pub fn count_trails(map: &HashMap<Point, u32>, trailhead: &Point) -> u32 {
     let mut summits = HashSet::new();
     let mut frontier = vec![(*trailhead, 0)];
     while let Some((position, height)) = frontier.pop() {
@@ -75,7 +119,8 @@ fn count_trails(map: &HashMap<Point, u32>, trailhead: &Point) -> u32 {
     summits.len() as u32
 }
 
-fn rate_trailhead(map: &HashMap<Point, u32>, trailhead: &Point, current_height: u32) -> u32 {
+/// Number of distinct hiking trails from `trailhead` to any summit
+pub fn rate_trailhead(map: &HashMap<Point, u32>, trailhead: &Point, current_height: u32) -> u32 {
     if current_height == 9 {
         return 1;
     }
@@ -84,7 +129,7 @@ fn rate_trailhead(map: &HashMap<Point, u32>, trailhead: &Point, current_height:
     for dir in Direction::all_dirs().iter() {
         let neighbor = *trailhead + dir.as_point();
         match map.get(&neighbor) {
-            Some(v) if *v == current_height + 1 => total += rate_trailhead(&map, &neighbor, *v),
+            Some(v) if *v == current_height + 1 => total += rate_trailhead(map, &neighbor, *v),
             _ => {}
         }
     }
@@ -95,30 +140,45 @@ fn rate_trailhead(map: &HashMap<Point, u32>, trailhead: &Point, current_height:
 pub struct HoofItSolution;
 
 impl Solution for HoofItSolution {
-    fn part1(puzzle_input: String) -> String {
-        let trail_map = parse_input(puzzle_input);
+    fn part1(puzzle_input: String) -> Answer {
+        let trail_map: TrailMap = puzzle_input.parse().unwrap();
 
-        let trailheads = trail_map.iter().filter_map(|(p, v)| match v {
-            0 => Some(p),
-            _ => None,
-        });
+        trail_map.total_score().into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let trail_map: TrailMap = puzzle_input.parse().unwrap();
 
-        trailheads
-            .map(|t| count_trails(&trail_map, t))
-            .sum::<u32>()
-            .to_string()
+        trail_map.total_rating().into()
     }
-    fn part2(puzzle_input: String) -> String {
-        let trail_map = parse_input(puzzle_input);
+}
 
-        let trailheads = trail_map.iter().filter_map(|(p, v)| match v {
-            0 => Some(p),
-            _ => None,
-        });
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732";
+
+    #[test]
+    fn parses_map() {
+        let map: TrailMap = EXAMPLE.parse().unwrap();
+        assert_eq!(map.heights.get(&Point(0, 0)), Some(&8));
+        assert_eq!(map.trailheads().count(), 9);
+
+        assert_eq!("01\n2.".parse::<TrailMap>().unwrap_err().line, 2);
+    }
 
-        trailheads
-            .map(|t| rate_trailhead(&trail_map, t, 0))
-            .sum::<u32>()
-            .to_string()
+    #[test]
+    fn example() {
+        let map: TrailMap = EXAMPLE.parse().unwrap();
+        assert_eq!(map.total_score(), 36);
+        assert_eq!(map.total_rating(), 81);
     }
 }
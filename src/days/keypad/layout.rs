@@ -0,0 +1,125 @@
+//! Keypad layouts read from a picture of the keys.
+//!
+//! Each line is a row of the keypad and each character in it a key, with spaces for gaps. Any
+//! position without a key is a gap, so rows can be ragged:
+//!
+//! ```text
+//! 789
+//! 456
+//! 123
+//!  0A
+//! ```
+
+use std::{collections::HashMap, str::FromStr};
+
+use super::Point;
+use crate::parse::{self, ParseError};
+
+/// The door's numeric keypad
+pub const NUMERIC: &str = "789\n456\n123\n 0A";
+
+/// The keypad robots are driven with
+pub const DIRECTIONAL: &str = " ^A\n<v>";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    /// In reading order
+    keys: Vec<char>,
+    positions: HashMap<char, Point>,
+    at: HashMap<Point, char>,
+}
+
+impl Layout {
+    pub fn numeric() -> Layout {
+        NUMERIC.parse().expect("numeric layout is valid")
+    }
+
+    pub fn directional() -> Layout {
+        DIRECTIONAL.parse().expect("directional layout is valid")
+    }
+
+    /// Every key, row by row from the top left
+    pub fn keys(&self) -> &[char] {
+        &self.keys
+    }
+
+    /// Where `key` comes in [`Layout::keys`]
+    pub fn index(&self, key: char) -> Option<usize> {
+        self.keys.iter().position(|&k| k == key)
+    }
+
+    pub fn contains(&self, key: char) -> bool {
+        self.positions.contains_key(&key)
+    }
+
+    pub fn position(&self, key: char) -> Option<Point> {
+        self.positions.get(&key).copied()
+    }
+
+    /// The key at `point`, `None` for gaps and anywhere off the keypad
+    pub fn key_at(&self, point: Point) -> Option<char> {
+        self.at.get(&point).copied()
+    }
+}
+
+impl FromStr for Layout {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut layout = Layout {
+            keys: Vec::new(),
+            positions: HashMap::new(),
+            at: HashMap::new(),
+        };
+
+        for (y, line) in parse::lines(s).enumerate() {
+            for (x, key) in line.text.chars().enumerate() {
+                if key == ' ' {
+                    continue;
+                }
+
+                let point = Point(x as i32, y as i32);
+                if layout.positions.insert(key, point).is_some() {
+                    return Err(line.error(format!("{key:?} is on the keypad more than once")));
+                }
+                layout.keys.push(key);
+                layout.at.insert(point, key);
+            }
+        }
+
+        if layout.keys.is_empty() {
+            return Err(ParseError::new(1, "keypad has no keys"));
+        }
+
+        Ok(layout)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_layouts() {
+        let numeric = Layout::numeric();
+        assert_eq!(numeric.keys().len(), 11);
+        assert_eq!(numeric.position('A'), Some(Point(2, 3)));
+        assert_eq!(numeric.key_at(Point(0, 3)), None);
+        assert_eq!(numeric.key_at(Point(1, 3)), Some('0'));
+
+        let directional = Layout::directional();
+        assert_eq!(directional.keys(), &['^', 'A', '<', 'v', '>']);
+        assert_eq!(directional.key_at(Point(0, 0)), None);
+        assert_eq!(directional.key_at(Point(3, 1)), None);
+    }
+
+    #[test]
+    fn rejects_bad_layouts() {
+        assert_eq!("ab\n\nca".parse::<Layout>().unwrap_err().line, 3);
+        assert_eq!(
+            "ab\nca".parse::<Layout>().unwrap_err().message,
+            "'a' is on the keypad more than once"
+        );
+        assert!("  \n ".parse::<Layout>().is_err());
+    }
+}
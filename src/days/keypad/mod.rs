@@ -0,0 +1,428 @@
+//! Day 21: Keypad Conundrum
+
+pub mod layout;
+pub mod sequence;
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt,
+    ops::{Add, Sub},
+    str::FromStr,
+};
+
+use layout::Layout;
+
+use crate::{
+    answer::Answer,
+    parse::{self, Line, ParseError, ParseResult},
+    solution::Solution,
+};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Point(pub i32, pub i32);
+
+impl Add for Point {
+    type Output = Point;
+    fn add(self, rhs: Self) -> Self::Output {
+        Point(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+/// Why a chain of keypads can't type something
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeypadError {
+    /// Keypad number `layout` in the chain doesn't have `key`
+    MissingKey { layout: usize, key: char },
+    /// Every way from `from` to `to` on keypad number `layout` crosses a gap
+    Unreachable { layout: usize, from: char, to: char },
+    /// Press number `press` moved the arm over keypad `layout` into a gap
+    Gap { layout: usize, press: usize },
+    /// `key` on keypad `layout` doesn't steer the keypad before it
+    NotAControl { layout: usize, key: char },
+    /// Spelling out a sequence this many presses long isn't worth it
+    TooLong { presses: u64 },
+}
+
+impl fmt::Display for KeypadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeypadError::MissingKey { layout, key } => {
+                write!(f, "keypad {layout} has no {key:?} key")
+            }
+            KeypadError::Unreachable { layout, from, to } => {
+                write!(f, "can't get from {from:?} to {to:?} on keypad {layout}")
+            }
+            KeypadError::Gap { layout, press } => {
+                write!(
+                    f,
+                    "press {press} moves the arm over keypad {layout} into a gap"
+                )
+            }
+            KeypadError::NotAControl { layout, key } => {
+                write!(f, "{key:?} on keypad {layout} doesn't do anything")
+            }
+            KeypadError::TooLong { presses } => write!(f, "{presses} presses is too many to list"),
+        }
+    }
+}
+
+impl std::error::Error for KeypadError {}
+
+/// The keys which move an arm, and which way
+const MOVES: [(char, Point); 4] = [
+    ('^', Point(0, -1)),
+    ('>', Point(1, 0)),
+    ('v', Point(0, 1)),
+    ('<', Point(-1, 0)),
+];
+
+/// Presses by hand to move between two keys on a keypad and press the second, `None` where the
+/// gaps make it impossible. Keys are numbered by their place in [`Layout::keys`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostTable {
+    keys: usize,
+    costs: Vec<Option<u64>>,
+}
+
+impl CostTable {
+    pub fn get(&self, from: usize, to: usize) -> Option<u64> {
+        self.costs[from * self.keys + to]
+    }
+}
+
+/// The cheapest way to move the arm over `keypad` from `from` to `to` and press it, as its cost
+/// and the keys pressed on `controller` to do it. `costs` are the controller's own costs.
+fn search(
+    keypad: &Layout,
+    controller: &Layout,
+    costs: &CostTable,
+    from: char,
+    to: char,
+) -> Option<(u64, Vec<char>)> {
+    let (start, goal) = (keypad.position(from)?, keypad.position(to)?);
+    let cost = |arm, key| costs.get(controller.index(arm)?, controller.index(key)?);
+
+    // Dijkstra over where this arm is and which key the arm controlling it is on. Pressing is a
+    // step to a finished state, since what it costs depends on that other arm.
+    let mut queue = BinaryHeap::from([Reverse((0, false, start, 'A', (start, 'A')))]);
+    let mut parents = HashMap::new();
+    while let Some(Reverse((presses, pressed, position, arm, parent))) = queue.pop() {
+        if pressed {
+            let mut keys = vec!['A'];
+            let mut state = (position, arm);
+            while state != (start, 'A') {
+                keys.push(state.1);
+                state = parents[&state];
+            }
+            keys.reverse();
+            return Some((presses, keys));
+        }
+        if parents.contains_key(&(position, arm)) {
+            continue;
+        }
+        parents.insert((position, arm), parent);
+
+        if position == goal {
+            if let Some(cost) = cost(arm, 'A') {
+                let presses = u64::saturating_add(presses, cost);
+                queue.push(Reverse((presses, true, position, arm, parent)));
+            }
+        }
+        for (key, step) in MOVES {
+            let next = position + step;
+            if keypad.key_at(next).is_none() {
+                continue;
+            }
+            if let Some(cost) = cost(arm, key) {
+                let presses = u64::saturating_add(presses, cost);
+                queue.push(Reverse((presses, false, next, key, (position, arm))));
+            }
+        }
+    }
+
+    None
+}
+
+/// Keypads operating each other. The code is typed on the first keypad, each of the others works
+/// the arm over the one before it, and the last one is pressed by hand. Every arm starts on `A`.
+///
+/// The costs for every pair of keys are worked out up front, starting from the keypad pressed by
+/// hand, so each keypad's table only needs the one after it. Costs saturate at `u64::MAX` rather
+/// than overflowing for very long chains.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeypadSolver {
+    layouts: Vec<Layout>,
+    /// One per keypad
+    tables: Vec<CostTable>,
+}
+
+impl KeypadSolver {
+    /// Every keypad needs an `A` key, and all but the first need the arrow keys to steer the one
+    /// before it
+    pub fn new(layouts: Vec<Layout>) -> Result<KeypadSolver, KeypadError> {
+        let Some(last) = layouts.last() else {
+            return Err(KeypadError::MissingKey {
+                layout: 0,
+                key: 'A',
+            });
+        };
+
+        for (i, layout) in layouts.iter().enumerate() {
+            let required = if i == 0 { "A" } else { "A^>v<" };
+            if let Some(key) = required.chars().find(|&key| !layout.contains(key)) {
+                return Err(KeypadError::MissingKey { layout: i, key });
+            }
+        }
+
+        let keys = last.keys().len();
+        let mut tables = vec![CostTable {
+            keys,
+            costs: vec![Some(1); keys * keys],
+        }];
+        for pair in layouts.windows(2).rev() {
+            let (keypad, controller) = (&pair[0], &pair[1]);
+            let costs = tables.last().expect("starts with the last keypad's");
+            let table = CostTable {
+                keys: keypad.keys().len(),
+                costs: keypad
+                    .keys()
+                    .iter()
+                    .flat_map(|&from| keypad.keys().iter().map(move |&to| (from, to)))
+                    .map(|(from, to)| {
+                        search(keypad, controller, costs, from, to).map(|(cost, _)| cost)
+                    })
+                    .collect(),
+            };
+            tables.push(table);
+        }
+        tables.reverse();
+
+        Ok(KeypadSolver { layouts, tables })
+    }
+
+    /// The door's numeric keypad, with `robots` directional keypads worked by robots and the one
+    /// you press between you and it
+    pub fn puzzle(robots: usize) -> KeypadSolver {
+        let mut layouts = vec![Layout::numeric()];
+        layouts.extend(std::iter::repeat_n(Layout::directional(), robots + 1));
+
+        KeypadSolver::new(layouts).expect("puzzle keypads have every key")
+    }
+
+    pub fn layouts(&self) -> &[Layout] {
+        &self.layouts
+    }
+
+    /// The costs for keypad `layout`
+    pub fn table(&self, layout: usize) -> &CostTable {
+        &self.tables[layout]
+    }
+
+    /// Fewest presses by hand to move the arm over keypad `layout` from `from` to `to` and press
+    /// it, `None` if there's no way around the gaps
+    pub fn cost(&self, layout: usize, from: char, to: char) -> Option<u64> {
+        let keypad = &self.layouts[layout];
+        self.tables[layout].get(keypad.index(from)?, keypad.index(to)?)
+    }
+
+    /// [`KeypadSolver::cost`] along with the keys pressed on the keypad after `layout`, which
+    /// can't be the last keypad
+    fn search(&self, layout: usize, from: char, to: char) -> Option<(u64, Vec<char>)> {
+        search(
+            &self.layouts[layout],
+            &self.layouts[layout + 1],
+            &self.tables[layout + 1],
+            from,
+            to,
+        )
+    }
+
+    /// Fewest presses by hand to type `code` on the first keypad, `u64::MAX` if there are more
+    /// than that
+    pub fn presses(&self, code: &str) -> Result<u64, KeypadError> {
+        let mut total = 0;
+        let mut prev = 'A';
+        for key in code.chars() {
+            if !self.layouts[0].contains(key) {
+                return Err(KeypadError::MissingKey { layout: 0, key });
+            }
+            let cost = self.cost(0, prev, key).ok_or(KeypadError::Unreachable {
+                layout: 0,
+                from: prev,
+                to: key,
+            })?;
+            total = u64::saturating_add(total, cost);
+            prev = key;
+        }
+
+        Ok(total)
+    }
+}
+
+/// A code to type on the door, along with its numeric part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoorCode {
+    pub code: String,
+    pub value: u64,
+}
+
+impl DoorCode {
+    /// Length of the shortest sequence typing this code times its numeric part. Like
+    /// [`KeypadSolver::presses`] this stops at `u64::MAX` for chains too deep to count.
+    pub fn complexity(&self, solver: &KeypadSolver) -> Result<u64, KeypadError> {
+        Ok(solver.presses(&self.code)?.saturating_mul(self.value))
+    }
+}
+
+impl FromStr for DoorCode {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line::new(s);
+        let numpad = Layout::numeric();
+
+        if let Some(c) = s.chars().find(|&c| !numpad.contains(c)) {
+            return Err(line.error(format!("{c:?} is not on the keypad")));
+        }
+        let digits = s
+            .strip_suffix('A')
+            .ok_or_else(|| line.error("code should end with A"))?;
+
+        Ok(DoorCode {
+            code: s.to_string(),
+            value: Line::new(digits).parse()?,
+        })
+    }
+}
+
+pub fn parse_input(puzzle_input: &str) -> ParseResult<Vec<DoorCode>> {
+    parse::parse_lines(puzzle_input)
+}
+
+pub struct Keypad;
+
+impl Solution for Keypad {
+    fn part1(puzzle_input: String) -> Answer {
+        let codes = parse_input(&puzzle_input).unwrap();
+        let solver = KeypadSolver::puzzle(2);
+
+        codes
+            .iter()
+            .map(|code| code.complexity(&solver).unwrap())
+            .sum::<u64>()
+            .into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let codes = parse_input(&puzzle_input).unwrap();
+        let solver = KeypadSolver::puzzle(25);
+
+        codes
+            .iter()
+            .map(|code| code.complexity(&solver).unwrap())
+            .sum::<u64>()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_depth_1() {
+        let solver = KeypadSolver::puzzle(0);
+        assert_eq!(solver.cost(0, 'A', '3'), Some(2));
+        assert_eq!(solver.cost(0, 'A', '2'), Some(3));
+        assert_eq!(solver.cost(0, 'A', '7'), Some(6));
+        assert_eq!(solver.cost(0, 'A', 'A'), Some(1));
+        assert_eq!(solver.cost(0, '7', '7'), Some(1));
+    }
+
+    #[test]
+    fn avoids_gaps_on_every_keypad() {
+        let solver = KeypadSolver::new(vec![Layout::directional(); 3]).unwrap();
+        // v<<A, since going left first crosses the gap
+        assert_eq!(solver.cost(0, 'A', '<'), Some(10));
+
+        // The only way from A to 1 is around the bottom row
+        let around: Layout = "A 1\n234".parse().unwrap();
+        let solver = KeypadSolver::new(vec![around, Layout::directional()]).unwrap();
+        assert_eq!(solver.presses("1A"), Ok(10));
+    }
+
+    #[test]
+    fn solves_long_chains() {
+        let solver = KeypadSolver::puzzle(25);
+        let numeric = &solver.layouts()[0];
+        assert_eq!(
+            solver
+                .table(0)
+                .get(numeric.index('A').unwrap(), numeric.index('0').unwrap()),
+            solver.cost(0, 'A', '0')
+        );
+        assert_eq!(solver.presses("029A"), Ok(82050061710));
+
+        // Far past what fits in a u64
+        assert_eq!(KeypadSolver::puzzle(100).presses("029A"), Ok(u64::MAX));
+        let code: DoorCode = "029A".parse().unwrap();
+        assert_eq!(code.complexity(&KeypadSolver::puzzle(100)), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn reports_bad_chains() {
+        assert_eq!(
+            KeypadSolver::new(vec![Layout::numeric(), Layout::numeric()]),
+            Err(KeypadError::MissingKey {
+                layout: 1,
+                key: '^'
+            })
+        );
+
+        let split: Layout = "A 1".parse().unwrap();
+        let solver = KeypadSolver::new(vec![split, Layout::directional()]).unwrap();
+        assert_eq!(
+            solver.presses("A1"),
+            Err(KeypadError::Unreachable {
+                layout: 0,
+                from: 'A',
+                to: '1'
+            })
+        );
+        assert_eq!(
+            solver.presses("2"),
+            Err(KeypadError::MissingKey {
+                layout: 0,
+                key: '2'
+            })
+        );
+    }
+
+    #[test]
+    fn parses_codes() {
+        let code: DoorCode = "029A".parse().unwrap();
+        assert_eq!(code.value, 29);
+
+        assert!("029".parse::<DoorCode>().is_err());
+        assert!("02BA".parse::<DoorCode>().is_err());
+        assert_eq!(parse_input("029A\n980A\n17A9").unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn example() {
+        let codes = parse_input("029A\n980A\n179A\n456A\n379A").unwrap();
+        let solver = KeypadSolver::puzzle(2);
+
+        assert_eq!(solver.presses("029A"), Ok(68));
+        let total: u64 = codes.iter().map(|c| c.complexity(&solver).unwrap()).sum();
+        assert_eq!(total, 126384);
+    }
+}
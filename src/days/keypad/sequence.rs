@@ -0,0 +1,167 @@
+//! Spelling out the presses behind [`KeypadSolver::presses`], and checking a sequence of presses
+//! does what it should.
+//!
+//! Every optimal move on one keypad starts and ends with the arm controlling it on `A`, so an
+//! optimal sequence for the whole chain is built a keypad at a time by swapping each key for the
+//! presses the search behind [`KeypadSolver::cost`] found for it. Sequences grow a couple of
+//! times longer with every keypad, so this is only practical for short chains.
+
+use super::{KeypadError, KeypadSolver, MOVES};
+
+/// Longest sequence [`sequences`] will spell out
+pub const SEQUENCE_LIMIT: u64 = 1 << 20;
+
+/// An optimal sequence of presses on each keypad of the chain for typing `code`, starting with
+/// `code` itself and ending with the presses made by hand
+pub fn sequences(solver: &KeypadSolver, code: &str) -> Result<Vec<String>, KeypadError> {
+    let presses = solver.presses(code)?;
+    if presses > SEQUENCE_LIMIT {
+        return Err(KeypadError::TooLong { presses });
+    }
+
+    let mut levels = vec![code.to_string()];
+    for layout in 0..solver.layouts.len() - 1 {
+        let mut next = String::new();
+        let mut prev = 'A';
+        for key in levels[layout].chars() {
+            let unreachable = KeypadError::Unreachable {
+                layout,
+                from: prev,
+                to: key,
+            };
+            let (_, keys) = solver.search(layout, prev, key).ok_or(unreachable)?;
+            next.extend(keys);
+            prev = key;
+        }
+        levels.push(next);
+    }
+
+    Ok(levels)
+}
+
+/// One of the shortest sequences of presses by hand which types `code`
+pub fn shortest_sequence(solver: &KeypadSolver, code: &str) -> Result<String, KeypadError> {
+    Ok(sequences(solver, code)?
+        .pop()
+        .expect("there's always the code itself"))
+}
+
+/// Presses `presses` by hand on the last keypad and returns what gets typed on the first. Fails if
+/// an arm is moved over a gap.
+pub fn replay(solver: &KeypadSolver, presses: &str) -> Result<String, KeypadError> {
+    let layouts = &solver.layouts;
+    let last = layouts.len() - 1;
+    let mut arms: Vec<_> = layouts
+        .iter()
+        .map(|layout| layout.position('A').expect("chains have A on every keypad"))
+        .collect();
+
+    let mut typed = String::new();
+    for (press, key) in presses.chars().enumerate() {
+        if !layouts[last].contains(key) {
+            return Err(KeypadError::MissingKey { layout: last, key });
+        }
+
+        // Each A passes the key under the next arm on down the chain
+        let (mut layout, mut key) = (last, key);
+        while layout > 0 && key == 'A' {
+            layout -= 1;
+            key = layouts[layout]
+                .key_at(arms[layout])
+                .expect("arms only stop on keys");
+        }
+        if layout == 0 {
+            typed.push(key);
+            continue;
+        }
+
+        let below = layout - 1;
+        let (_, step) = MOVES
+            .iter()
+            .find(|(control, _)| *control == key)
+            .ok_or(KeypadError::NotAControl { layout, key })?;
+        arms[below] = arms[below] + *step;
+        if layouts[below].key_at(arms[below]).is_none() {
+            return Err(KeypadError::Gap {
+                layout: below,
+                press,
+            });
+        }
+    }
+
+    Ok(typed)
+}
+
+/// Whether pressing `presses` by hand types exactly `code`, without crossing any gaps
+pub fn types(solver: &KeypadSolver, presses: &str, code: &str) -> bool {
+    replay(solver, presses).is_ok_and(|typed| typed == code)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::layout::Layout;
+    use super::*;
+
+    #[test]
+    fn rebuilds_optimal_sequences() {
+        let solver = KeypadSolver::puzzle(2);
+
+        for code in ["029A", "980A", "179A", "456A", "379A"] {
+            let levels = sequences(&solver, code).unwrap();
+            assert_eq!(levels.len(), 4);
+            assert_eq!(levels[0], code);
+            assert_eq!(shortest_sequence(&solver, code).as_ref(), Ok(&levels[3]));
+            assert_eq!(levels[3].len() as u64, solver.presses(code).unwrap());
+
+            // Each level types the one before it on the shorter chain
+            for (depth, level) in levels.iter().enumerate().skip(1) {
+                let shorter = KeypadSolver::new(solver.layouts()[..=depth].to_vec()).unwrap();
+                assert_eq!(replay(&shorter, level), Ok(code.to_string()));
+            }
+        }
+    }
+
+    #[test]
+    fn replays_sequences() {
+        let solver = KeypadSolver::puzzle(2);
+        let example = "<vA<AA>>^AvAA<^A>A<v<A>>^AvA^A<vA>^A<v<A>^A>AAvA^A<v<A>A>^AAAvA<^A>A";
+        assert_eq!(replay(&solver, example), Ok("029A".to_string()));
+        assert!(types(&solver, example, "029A"));
+        assert!(!types(&solver, example, "029"));
+
+        // Left from 0 is the numeric keypad's gap
+        let direct = KeypadSolver::puzzle(0);
+        assert_eq!(
+            replay(&direct, "<<A"),
+            Err(KeypadError::Gap {
+                layout: 0,
+                press: 1
+            })
+        );
+        assert_eq!(
+            replay(&direct, "<A7"),
+            Err(KeypadError::MissingKey {
+                layout: 1,
+                key: '7'
+            })
+        );
+
+        let extra: Layout = " ^A\n<v>\n x ".parse().unwrap();
+        let solver = KeypadSolver::new(vec![Layout::numeric(), extra]).unwrap();
+        assert_eq!(
+            replay(&solver, "<Ax"),
+            Err(KeypadError::NotAControl {
+                layout: 1,
+                key: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn refuses_huge_sequences() {
+        assert!(matches!(
+            sequences(&KeypadSolver::puzzle(25), "029A"),
+            Err(KeypadError::TooLong { .. })
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+//! Finding sets of computers which are all linked to each other.
+//!
+//! Cliques are only ever extended with computers which have a higher id than everything already in
+//! them, so each one is found exactly once, already sorted, without remembering the ones seen so
+//! far.
+
+use super::{nodeset::NodeSet, Network, NodeId};
+
+/// Each computer's neighbours with a higher id
+fn forward(network: &Network) -> Vec<NodeSet> {
+    (0..network.node_count())
+        .map(|id| network.neighbours(id).above(id))
+        .collect()
+}
+
+/// Number of sets of three computers which are all linked
+pub fn count_triangles(network: &Network) -> usize {
+    let forward = forward(network);
+    forward
+        .iter()
+        .map(|later| {
+            later
+                .iter()
+                .map(|b| later.intersection_len(&forward[b]))
+                .sum::<usize>()
+        })
+        .sum()
+}
+
+/// Every set of `k` computers which are all linked to each other, each sorted by id
+pub fn k_cliques(network: &Network, k: usize) -> Vec<Vec<NodeId>> {
+    let forward = forward(network);
+    let mut cliques = Vec::new();
+    extend(
+        &forward,
+        k,
+        &mut Vec::with_capacity(k),
+        &NodeSet::full(network.node_count()),
+        &mut cliques,
+    );
+    cliques
+}
+
+/// Collects the `k` cliques made by adding computers from `candidates` to `clique`
+fn extend(
+    forward: &[NodeSet],
+    k: usize,
+    clique: &mut Vec<NodeId>,
+    candidates: &NodeSet,
+    cliques: &mut Vec<Vec<NodeId>>,
+) {
+    if clique.len() == k {
+        cliques.push(clique.clone());
+        return;
+    }
+    if clique.len() + candidates.len() < k {
+        return;
+    }
+
+    for node in candidates.iter() {
+        clique.push(node);
+        extend(
+            forward,
+            k,
+            clique,
+            &candidates.intersection(&forward[node]),
+            cliques,
+        );
+        clique.pop();
+    }
+}
+
+/// The computers in the largest fully linked set, sorted by id. If there are several the same
+/// size, the first one found wins.
+pub fn largest_clique(network: &Network) -> Vec<NodeId> {
+    let count = network.node_count();
+    let mut largest = Vec::new();
+    bron_kerbosch(
+        network,
+        &mut Vec::new(),
+        NodeSet::full(count),
+        NodeSet::new(count),
+        &mut largest,
+    );
+    largest.sort();
+    largest
+}
+
+/// Searches the maximal cliques extending `clique` with computers from `candidates`, excluding
+/// those in `excluded`, for one larger than `largest`
+fn bron_kerbosch(
+    network: &Network,
+    clique: &mut Vec<NodeId>,
+    mut candidates: NodeSet,
+    mut excluded: NodeSet,
+    largest: &mut Vec<NodeId>,
+) {
+    if candidates.is_empty() {
+        if excluded.is_empty() && clique.len() > largest.len() {
+            *largest = clique.clone();
+        }
+        return;
+    }
+    if clique.len() + candidates.len() <= largest.len() {
+        return;
+    }
+
+    // Pivoting on the computer with the most candidate neighbours leaves the fewest to branch on
+    let pivot = candidates
+        .union(&excluded)
+        .iter()
+        .max_by_key(|&node| candidates.intersection_len(network.neighbours(node)))
+        .expect("candidates isn't empty");
+    for node in candidates.difference(network.neighbours(pivot)).iter() {
+        let neighbours = network.neighbours(node);
+        clique.push(node);
+        bron_kerbosch(
+            network,
+            clique,
+            candidates.intersection(neighbours),
+            excluded.intersection(neighbours),
+            largest,
+        );
+        clique.pop();
+        candidates.remove(node);
+        excluded.insert(node);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Two triangles sharing the edge b-c, plus a 4-clique and a loose pair
+    fn network() -> Network {
+        Network::new(&[
+            ("a", "b"),
+            ("a", "c"),
+            ("b", "c"),
+            ("b", "d"),
+            ("c", "d"),
+            ("w", "x"),
+            ("w", "y"),
+            ("w", "z"),
+            ("x", "y"),
+            ("x", "z"),
+            ("y", "z"),
+            ("p", "q"),
+        ])
+    }
+
+    #[test]
+    fn counts_triangles() {
+        let network = network();
+        assert_eq!(count_triangles(&network), 6);
+        assert_eq!(k_cliques(&network, 3).len(), 6);
+        assert_eq!(count_triangles(&Network::new(&[])), 0);
+    }
+
+    #[test]
+    fn enumerates_k_cliques() {
+        let network = network();
+        let named = |k| -> Vec<Vec<&str>> {
+            k_cliques(&network, k)
+                .iter()
+                .map(|clique| network.names(clique))
+                .collect()
+        };
+
+        assert_eq!(named(0), vec![Vec::<&str>::new()]);
+        assert_eq!(named(1).len(), network.node_count());
+        assert_eq!(named(2).len(), network.edges.len());
+        assert_eq!(named(3)[..2], [vec!["a", "b", "c"], vec!["b", "c", "d"]]);
+        assert_eq!(named(4), vec![vec!["w", "x", "y", "z"]]);
+        assert!(named(5).is_empty());
+    }
+
+    #[test]
+    fn finds_largest_clique() {
+        let network = network();
+        assert_eq!(
+            network.names(&largest_clique(&network)),
+            vec!["w", "x", "y", "z"]
+        );
+        assert!(largest_clique(&Network::new(&[])).is_empty());
+    }
+}
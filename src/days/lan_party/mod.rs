@@ -0,0 +1,308 @@
+//! Day 23: LAN Party
+
+pub mod cliques;
+pub mod nodeset;
+
+use std::str::FromStr;
+
+use nodeset::NodeSet;
+
+use crate::{
+    answer::Answer,
+    parse::{self, Line, ParseError, ParseResult},
+    solution::Solution,
+};
+
+/// A computer, numbered by where its name comes in alphabetical order
+pub type NodeId = usize;
+pub type Edge = (NodeId, NodeId);
+
+/// How many computers each computer is linked to
+#[derive(Debug, Clone, PartialEq)]
+pub struct DegreeStats {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    /// How many computers have each degree, indexed by degree
+    pub histogram: Vec<usize>,
+}
+
+/// The network map of connections between computers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    /// Sorted, so sorting ids sorts the computers by name too
+    names: Vec<String>,
+    pub edges: Vec<Edge>,
+    /// Indexed by id
+    adjacency: Vec<NodeSet>,
+}
+
+impl Network {
+    /// The network with a link between each pair of names. A link given again either way round
+    /// is only kept once, and links from a computer to itself are left out.
+    pub fn new(links: &[(&str, &str)]) -> Network {
+        let mut names: Vec<String> = links
+            .iter()
+            .flat_map(|&(a, b)| [a, b])
+            .map(String::from)
+            .collect();
+        names.sort();
+        names.dedup();
+
+        let id = |name: &str| names.binary_search_by(|n| n.as_str().cmp(name)).unwrap();
+        let mut adjacency = vec![NodeSet::new(names.len()); names.len()];
+        let mut edges: Vec<Edge> = Vec::new();
+        for &(a, b) in links {
+            let (a, b) = (id(a), id(b));
+            if a != b && adjacency[a].insert(b) {
+                adjacency[b].insert(a);
+                edges.push((a, b));
+            }
+        }
+
+        Network {
+            names,
+            edges,
+            adjacency,
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id]
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.names.binary_search_by(|n| n.as_str().cmp(name)).ok()
+    }
+
+    /// The name of each computer in `ids`
+    pub fn names(&self, ids: &[NodeId]) -> Vec<&str> {
+        ids.iter().map(|&id| self.name(id)).collect()
+    }
+
+    /// The computers linked to `id`
+    pub fn neighbours(&self, id: NodeId) -> &NodeSet {
+        &self.adjacency[id]
+    }
+
+    pub fn degree(&self, id: NodeId) -> usize {
+        self.adjacency[id].len()
+    }
+
+    /// `None` for a network without any computers
+    pub fn degree_stats(&self) -> Option<DegreeStats> {
+        let degrees: Vec<usize> = (0..self.node_count()).map(|id| self.degree(id)).collect();
+        let max = *degrees.iter().max()?;
+
+        let mut histogram = vec![0; max + 1];
+        for &degree in &degrees {
+            histogram[degree] += 1;
+        }
+
+        Some(DegreeStats {
+            min: *degrees.iter().min()?,
+            max,
+            mean: degrees.iter().sum::<usize>() as f64 / degrees.len() as f64,
+            histogram,
+        })
+    }
+
+    /// Each group of computers which can reach each other, sorted by id, in order of their first
+    /// computer
+    pub fn components(&self) -> Vec<Vec<NodeId>> {
+        let mut unvisited = NodeSet::full(self.node_count());
+        let mut components = Vec::new();
+        while let Some(start) = unvisited.first() {
+            let mut component = NodeSet::new(self.node_count());
+            component.insert(start);
+            let mut frontier = component.clone();
+            while !frontier.is_empty() {
+                let reached = frontier
+                    .iter()
+                    .fold(NodeSet::new(self.node_count()), |reached, id| {
+                        reached.union(self.neighbours(id))
+                    });
+                frontier = reached.difference(&component);
+                component = component.union(&frontier);
+            }
+
+            unvisited = unvisited.difference(&component);
+            components.push(component.iter().collect());
+        }
+
+        components
+    }
+}
+
+fn parse_name<'a>(line: &Line, name: &'a str) -> ParseResult<&'a str> {
+    match name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '-') {
+        true => Err(line.error(format!("{name:?} isn't a computer name"))),
+        false => Ok(name),
+    }
+}
+
+impl FromStr for Network {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let links: Vec<(&str, &str)> = parse::lines(s)
+            .map(|line| {
+                let (a, b) = line.split_once("-")?;
+                let link = (parse_name(&line, a.text)?, parse_name(&line, b.text)?);
+                match link.0 == link.1 {
+                    true => Err(line.error(format!("{:?} is linked to itself", link.0))),
+                    false => Ok(link),
+                }
+            })
+            .collect::<ParseResult<_>>()?;
+
+        Ok(Network::new(&links))
+    }
+}
+
+pub struct LanParty;
+
+impl Solution for LanParty {
+    fn part1(puzzle_input: String) -> Answer {
+        let network: Network = puzzle_input.parse().unwrap();
+
+        cliques::k_cliques(&network, 3)
+            .iter()
+            .filter(|clique| {
+                network
+                    .names(clique)
+                    .iter()
+                    .any(|name| name.starts_with('t'))
+            })
+            .count()
+            .into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let network: Network = puzzle_input.parse().unwrap();
+
+        Answer::list(network.names(&cliques::largest_clique(&network)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn";
+
+    #[test]
+    fn parses_network() {
+        let network: Network = EXAMPLE.parse().unwrap();
+        assert_eq!(network.edges.len(), 32);
+        assert_eq!(network.node_count(), 16);
+        assert_eq!(network.degree(network.id("kh").unwrap()), 4);
+        assert_eq!(network.id("zz"), None);
+
+        assert_eq!("kh-tc\nqp-".parse::<Network>().unwrap_err().line, 2);
+        assert!("kh tc".parse::<Network>().is_err());
+        assert!("kh-t c".parse::<Network>().is_err());
+        assert_eq!(
+            "a-b-c".parse::<Network>().unwrap_err().message,
+            "\"b-c\" isn't a computer name"
+        );
+        assert_eq!(
+            "kh-tc\ntc-kh\nkh-tc"
+                .parse::<Network>()
+                .unwrap()
+                .edges
+                .len(),
+            1
+        );
+        assert_eq!(
+            "kh-kh".parse::<Network>().unwrap_err().message,
+            "\"kh\" is linked to itself"
+        );
+    }
+
+    #[test]
+    fn interns_long_names() {
+        let network: Network = "alpha-beta\nbeta-gamma\ngamma-alpha\ndelta-epsilon\nbeta-delta"
+            .parse()
+            .unwrap();
+        assert_eq!(network.node_count(), 5);
+        assert_eq!(network.name(0), "alpha");
+        assert_eq!(network.names(&[1, 4]), vec!["beta", "gamma"]);
+        assert_eq!(
+            network.names(&cliques::largest_clique(&network)),
+            vec!["alpha", "beta", "gamma"]
+        );
+    }
+
+    #[test]
+    fn describes_the_graph() {
+        let network: Network = "a-b\nb-c\nc-a\nc-d\nx-y".parse().unwrap();
+        assert_eq!(
+            network.degree_stats(),
+            Some(DegreeStats {
+                min: 1,
+                max: 3,
+                mean: 10.0 / 6.0,
+                histogram: vec![0, 3, 2, 1],
+            })
+        );
+        assert_eq!(Network::new(&[]).degree_stats(), None);
+
+        let components: Vec<_> = network
+            .components()
+            .iter()
+            .map(|component| network.names(component))
+            .collect();
+        assert_eq!(components, vec![vec!["a", "b", "c", "d"], vec!["x", "y"]]);
+        assert_eq!(cliques::count_triangles(&network), 1);
+    }
+
+    #[test]
+    fn example() {
+        let network: Network = EXAMPLE.parse().unwrap();
+        assert_eq!(cliques::count_triangles(&network), 12);
+        assert_eq!(cliques::k_cliques(&network, 3).len(), 12);
+        assert_eq!(
+            network.names(&cliques::largest_clique(&network)),
+            vec!["co", "de", "ka", "ta"]
+        );
+        assert_eq!(network.components().len(), 1);
+        assert_eq!(LanParty::part1(EXAMPLE.to_string()), "7");
+        assert_eq!(LanParty::part2(EXAMPLE.to_string()), "co,de,ka,ta");
+    }
+}
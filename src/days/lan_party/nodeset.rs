@@ -0,0 +1,177 @@
+//! Sets of computers packed into bits, one per [`NodeId`], so intersecting two neighbourhoods is a
+//! handful of word operations rather than a hash lookup per computer.
+
+use super::NodeId;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeSet {
+    words: Vec<u64>,
+}
+
+impl NodeSet {
+    /// An empty set with room for every id below `capacity`
+    pub fn new(capacity: usize) -> NodeSet {
+        NodeSet {
+            words: vec![0; capacity.div_ceil(WORD_BITS)],
+        }
+    }
+
+    /// Every id below `capacity`
+    pub fn full(capacity: usize) -> NodeSet {
+        let mut set = NodeSet::new(capacity);
+        for (i, word) in set.words.iter_mut().enumerate() {
+            let bits = (capacity - i * WORD_BITS).min(WORD_BITS);
+            *word = u64::MAX >> (WORD_BITS - bits);
+        }
+        set
+    }
+
+    /// Adds `id`, returning whether it's new
+    pub fn insert(&mut self, id: NodeId) -> bool {
+        let (word, bit) = (id / WORD_BITS, 1 << (id % WORD_BITS));
+        let new = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        new
+    }
+
+    /// Takes out `id`, returning whether it was there
+    pub fn remove(&mut self, id: NodeId) -> bool {
+        let had = self.contains(id);
+        if had {
+            self.words[id / WORD_BITS] &= !(1 << (id % WORD_BITS));
+        }
+        had
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.words
+            .get(id / WORD_BITS)
+            .is_some_and(|word| word & (1 << (id % WORD_BITS)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// The smallest id in the set
+    pub fn first(&self) -> Option<NodeId> {
+        self.iter().next()
+    }
+
+    /// Every id in the set, smallest first
+    pub fn iter(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    i * WORD_BITS + bit
+                })
+            })
+        })
+    }
+
+    /// The ids in the set greater than `id`
+    pub fn above(&self, id: NodeId) -> NodeSet {
+        let mut above = self.clone();
+        let (word, bit) = (id / WORD_BITS, id % WORD_BITS);
+        for (i, w) in above.words.iter_mut().enumerate().take(word + 1) {
+            match i == word {
+                true => *w &= (u64::MAX << bit) << 1,
+                false => *w = 0,
+            }
+        }
+        above
+    }
+
+    pub fn union(&self, other: &NodeSet) -> NodeSet {
+        self.combine(other, |a, b| a | b)
+    }
+
+    pub fn intersection(&self, other: &NodeSet) -> NodeSet {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// Size of the intersection, without building it
+    pub fn intersection_len(&self, other: &NodeSet) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    /// The ids in this set but not `other`
+    pub fn difference(&self, other: &NodeSet) -> NodeSet {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    /// Both sets must have come from the same network, so they have the same number of words
+    fn combine(&self, other: &NodeSet, op: impl Fn(u64, u64) -> u64) -> NodeSet {
+        NodeSet {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&a, &b)| op(a, b))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packs_ids() {
+        let mut set = NodeSet::new(130);
+        assert!(set.is_empty());
+        assert!(set.insert(3));
+        assert!(set.insert(64));
+        assert!(set.insert(129));
+        assert!(!set.insert(64));
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(129));
+        assert!(!set.contains(128));
+        assert!(!set.contains(500));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 64, 129]);
+        assert_eq!(set.first(), Some(3));
+
+        assert!(set.remove(3));
+        assert!(!set.remove(3));
+        assert_eq!(set.first(), Some(64));
+    }
+
+    #[test]
+    fn combines_sets() {
+        let full = NodeSet::full(130);
+        assert_eq!(full.len(), 130);
+        assert_eq!(full.iter().last(), Some(129));
+        assert_eq!(NodeSet::full(64).len(), 64);
+
+        let mut evens = NodeSet::new(130);
+        for id in (0..130).step_by(2) {
+            evens.insert(id);
+        }
+        assert_eq!(full.difference(&evens).len(), 65);
+        assert_eq!(full.intersection(&evens), evens);
+        assert_eq!(full.intersection_len(&evens), 65);
+        assert_eq!(evens.union(&full), full);
+
+        assert_eq!(full.above(63).first(), Some(64));
+        assert_eq!(full.above(63).len(), 66);
+        assert_eq!(full.above(0).len(), 129);
+        assert!(full.above(129).is_empty());
+    }
+}
@@ -1,11 +1,19 @@
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet, VecDeque};
+//! Day 22: Monkey Market
 
-use crate::solution::Solution;
+use std::{fmt, str::FromStr};
 
-const MAX_SECRET_SIZE: u64 = 16777216;
+use rayon::{iter::ParallelIterator, slice::ParallelSlice};
 
-fn step_number(secret: u64) -> u64 {
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError},
+    solution::Solution,
+};
+
+pub const MAX_SECRET_SIZE: u64 = 16777216;
+
+/// Generates the next secret number in a buyer's sequence
+pub fn step_number(secret: u64) -> u64 {
     let mut result = secret;
     result = result ^ (result * 64);
     result = result.rem_euclid(MAX_SECRET_SIZE);
@@ -19,7 +27,7 @@ fn step_number(secret: u64) -> u64 {
     result
 }
 
-fn nth_secret(secret: u64, n: u64) -> u64 {
+pub fn nth_secret(secret: u64, n: u64) -> u64 {
     let mut result = secret;
     for _ in 0..n {
         result = step_number(result)
@@ -27,65 +35,398 @@ fn nth_secret(secret: u64, n: u64) -> u64 {
     result
 }
 
-type History = (i8, i8, i8, i8);
+/// Undoes `value ^ (value << shift)` on 24 bits, each pass recovering another `shift` low bits
+fn unshift_left(value: u64, shift: u32) -> u64 {
+    let mut result = value;
+    for _ in 0..24 / shift {
+        result = (value ^ (result << shift)) % MAX_SECRET_SIZE;
+    }
+    result
+}
+
+/// Undoes `value ^ (value >> shift)`, each pass recovering another `shift` high bits
+fn unshift_right(value: u64, shift: u32) -> u64 {
+    let mut result = value;
+    for _ in 0..24 / shift {
+        result = value ^ (result >> shift);
+    }
+    result
+}
+
+/// The secret which comes before `secret`. Every step is a bijection on 24 bit numbers, and only
+/// the low 24 bits of a seed make a difference, so this finds the one seed below
+/// [`MAX_SECRET_SIZE`] which steps to `secret`.
+pub fn previous_secret(secret: u64) -> u64 {
+    let result = unshift_left(secret % MAX_SECRET_SIZE, 11);
+    let result = unshift_right(result, 5);
+    unshift_left(result, 6)
+}
+
+/// The secret `n` steps before `secret`, e.g. the seed of a buyer whose `n`th secret it is
+pub fn nth_previous_secret(secret: u64, n: u64) -> u64 {
+    (0..n).fold(secret, |secret, _| previous_secret(secret))
+}
+
+/// Number of steps before a buyer's secrets start repeating. Since stepping is a bijection the
+/// secrets go round in a loop, which takes in the seed once it's cut down to 24 bits.
+pub fn cycle_length(seed: u64) -> u64 {
+    let start = seed % MAX_SECRET_SIZE;
+    let mut secret = step_number(start);
+    let mut length = 1;
+    while secret != start {
+        secret = step_number(secret);
+        length += 1;
+    }
+
+    length
+}
+
+/// Every secret number a buyer has, starting with their seed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretStream {
+    secret: u64,
+}
+
+impl SecretStream {
+    pub fn new(seed: u64) -> SecretStream {
+        SecretStream { secret: seed }
+    }
+
+    /// The price the buyer offers with each secret, its last digit
+    pub fn prices(self) -> impl Iterator<Item = u8> {
+        self.map(|secret| (secret % 10) as u8)
+    }
+}
+
+impl Iterator for SecretStream {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let secret = self.secret;
+        self.secret = step_number(secret);
+        Some(secret)
+    }
+}
 
-fn analyze_nums(seed: u64, look_forward: u64, global_data: &mut HashMap<History, u64>) {
+/// Four consecutive price changes
+pub type History = (i8, i8, i8, i8);
+
+/// Number of possible histories, each change being between -9 and 9
+pub const HISTORIES: usize = 19 * 19 * 19 * 19;
+
+/// Where `history` goes in a [`HistoryTotals`], reading the changes as base 19 digits
+pub fn history_index(history: History) -> usize {
+    let (a, b, c, d) = history;
+    [a, b, c, d]
+        .iter()
+        .fold(0, |index, &change| index * 19 + (change + 9) as usize)
+}
+
+/// The history stored at `index` of a [`HistoryTotals`]
+pub fn index_history(index: usize) -> History {
+    let change = |place: u32| ((index / 19usize.pow(place)) % 19) as i8 - 9;
+    (change(3), change(2), change(1), change(0))
+}
+
+/// Bananas each change history would buy, summed over buyers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryTotals {
+    /// Indexed by [`history_index`]
+    totals: Vec<u64>,
+}
+
+impl Default for HistoryTotals {
+    fn default() -> HistoryTotals {
+        HistoryTotals {
+            totals: vec![0; HISTORIES],
+        }
+    }
+}
+
+impl HistoryTotals {
+    pub fn new() -> HistoryTotals {
+        HistoryTotals::default()
+    }
+
+    pub fn get(&self, history: History) -> u64 {
+        self.totals[history_index(history)]
+    }
+
+    /// Every history which buys anything, with its total
+    pub fn iter(&self) -> impl Iterator<Item = (History, u64)> + '_ {
+        self.totals
+            .iter()
+            .enumerate()
+            .filter(|(_, &total)| total > 0)
+            .map(|(index, &total)| (index_history(index), total))
+    }
+
+    /// The history buying the most bananas, the first in index order on a tie
+    pub fn best(&self) -> Option<(History, u64)> {
+        self.iter().fold(None, |best, (history, total)| match best {
+            Some((_, most)) if most >= total => best,
+            _ => Some((history, total)),
+        })
+    }
+
+    /// The `k` histories buying the most bananas, most first and in index order on a tie
+    pub fn top(&self, k: usize) -> Vec<(History, u64)> {
+        let mut top: Vec<_> = self.iter().collect();
+        top.sort_by(|(_, a), (_, b)| b.cmp(a));
+        top.truncate(k);
+        top
+    }
+
+    /// Adds in another set of totals
+    pub fn merge(mut self, other: HistoryTotals) -> HistoryTotals {
+        for (total, other) in self.totals.iter_mut().zip(other.totals) {
+            *total += other;
+        }
+        self
+    }
+}
+
+/// The histories a buyer has already sold at. Each buyer gets a new generation instead of
+/// clearing the whole table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeenHistories {
+    /// The generation each history was last seen in, indexed by [`history_index`]
+    stamps: Vec<u32>,
+    generation: u32,
+}
+
+impl Default for SeenHistories {
+    fn default() -> SeenHistories {
+        SeenHistories {
+            stamps: vec![0; HISTORIES],
+            // Ahead of every stamp, so nothing starts out seen
+            generation: 1,
+        }
+    }
+}
+
+impl SeenHistories {
+    pub fn new() -> SeenHistories {
+        SeenHistories::default()
+    }
+
+    /// Forgets every history seen so far
+    pub fn next_buyer(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        // Stamps from the last time round would look current again
+        if self.generation == 0 {
+            self.stamps.fill(0);
+            self.generation = 1;
+        }
+    }
+
+    /// Marks the history at `index` as seen, returning whether it's new for this buyer
+    pub fn insert(&mut self, index: usize) -> bool {
+        let new = self.stamps[index] != self.generation;
+        self.stamps[index] = self.generation;
+        new
+    }
+}
+
+/// Adds the price this buyer sells at for each change history, the first time it appears
+/// within `look_forward` secrets, into `totals`. `seen` is moved on to this buyer, so it can be
+/// reused across buyers.
+pub fn analyze_nums(
+    seed: u64,
+    look_forward: u64,
+    totals: &mut HistoryTotals,
+    seen: &mut SeenHistories,
+) {
+    seen.next_buyer();
     let mut secret = seed;
-    let mut prev: Option<u64> = None;
-    let mut price_history = Vec::new();
-    let mut seen = HashSet::new();
-    for _ in 0..look_forward + 1 {
-        if let Some(previous_secret) = prev {
-            let delta = secret.rem_euclid(10) as i8 - previous_secret.rem_euclid(10) as i8;
-            price_history.push(delta);
+    let mut price = secret % 10;
+    // The last four changes as base 19 digits, oldest first
+    let mut index = 0;
+    for step in 1..=look_forward {
+        secret = step_number(secret);
+        let next = secret % 10;
+        index = (index * 19 + (next + 9 - price) as usize) % HISTORIES;
+        price = next;
+
+        // If we've seen this history before, we won't ever get to sell it on this buyer
+        if step >= 4 && seen.insert(index) {
+            totals.totals[index] += price;
+        }
+    }
+}
+
+/// When a buyer sells for a given history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sale {
+    /// Which of the buyer's secrets they sell at, counting the seed as 0
+    pub index: u64,
+    pub price: u8,
+}
+
+/// The buyer's sale the first time `history` turns up within `look_forward` secrets
+pub fn first_sale(seed: u64, history: History, look_forward: u64) -> Option<Sale> {
+    let target = history_index(history);
+    let mut prices = SecretStream::new(seed).prices();
+    let mut price = prices.next()?;
+    let mut index = 0;
+    for (step, next) in (1..=look_forward).zip(prices) {
+        index = (index * 19 + next as usize + 9 - price as usize) % HISTORIES;
+        price = next;
+
+        if step >= 4 && index == target {
+            return Some(Sale { index: step, price });
         }
+    }
+
+    None
+}
+
+/// Sales shown by [`SequenceReport`]'s table
+const SHOWN_SALES: usize = 10;
+
+/// Histories [`MonkeyMarket`] lists alongside the winner
+const TOP_SEQUENCES: usize = 5;
+
+/// Writes a history the way the puzzle does, e.g. `-2,1,-1,3`
+fn format_history(history: History) -> String {
+    let (a, b, c, d) = history;
+    format!("{a},{b},{c},{d}")
+}
 
-        if let Some(chunk) = price_history.last_chunk::<4>() {
-            let key = (chunk[0], chunk[1], chunk[2], chunk[3]);
-            let value = secret.rem_euclid(10);
-
-            // If we've seen this history before, we won't ever get to sell it on this buyer
-            if !seen.contains(&key) {
-                global_data
-                    .entry(key)
-                    .and_modify(|num| *num += value)
-                    .or_insert(value);
-                seen.insert(key);
+/// Why the best change history wins: what it buys from each buyer, and the runners up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceReport {
+    pub best: History,
+    pub total: u64,
+    /// One per buyer, `None` for buyers who never see `best`
+    pub sales: Vec<Option<Sale>>,
+    /// The histories buying the most bananas with their totals, starting with `best`
+    pub top: Vec<(History, u64)>,
+}
+
+impl fmt::Display for SequenceReport {
+    /// The winner, a table of the top histories and one of the first few buyers' sales
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let selling = self.sales.iter().flatten().count();
+        writeln!(
+            f,
+            "{} buys {} bananas from {} of {} buyers",
+            format_history(self.best),
+            self.total,
+            selling,
+            self.sales.len()
+        )?;
+
+        writeln!(f)?;
+        writeln!(f, "{:<11} {:>6}", "sequence", "total")?;
+        for &(history, total) in &self.top {
+            writeln!(f, "{:<11} {:>6}", format_history(history), total)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{:<5} {:>5} {:>5}", "buyer", "price", "index")?;
+        for (buyer, sale) in self.sales.iter().enumerate().take(SHOWN_SALES) {
+            match sale {
+                Some(sale) => writeln!(f, "{:<5} {:>5} {:>5}", buyer, sale.price, sale.index)?,
+                None => writeln!(f, "{:<5} {:>5} {:>5}", buyer, "-", "-")?,
             }
         }
+        if self.sales.len() > SHOWN_SALES {
+            writeln!(f, "... and {} more", self.sales.len() - SHOWN_SALES)?;
+        }
 
-        prev = Some(secret);
-        secret = step_number(secret);
+        Ok(())
+    }
+}
+
+/// Buyers each thread takes at a time, enough to be worth the tables it has to set up
+const BUYERS_PER_TASK: usize = 256;
+
+/// The initial secret number of every buyer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Market {
+    pub buyers: Vec<u64>,
+}
+
+impl Market {
+    /// Sum of each buyer's `n`th secret number
+    pub fn secret_sum(&self, n: u64) -> u64 {
+        self.buyers
+            .iter()
+            .map(|&secret| nth_secret(secret, n))
+            .sum()
+    }
+
+    /// Total bananas each change history would buy over `look_forward` secrets, with the buyers
+    /// split across threads
+    pub fn history_totals(&self, look_forward: u64) -> HistoryTotals {
+        self.buyers
+            .par_chunks(BUYERS_PER_TASK)
+            .map(|buyers| {
+                let mut totals = HistoryTotals::new();
+                let mut seen = SeenHistories::new();
+                for &seed in buyers {
+                    analyze_nums(seed, look_forward, &mut totals, &mut seen);
+                }
+                totals
+            })
+            .reduce(HistoryTotals::new, HistoryTotals::merge)
+    }
+
+    /// Most bananas a single change history can buy
+    pub fn most_bananas(&self, look_forward: u64) -> u64 {
+        self.history_totals(look_forward)
+            .best()
+            .map_or(0, |(_, total)| total)
     }
 
-    assert_eq!(price_history.len(), look_forward as usize);
+    /// The best change history over `look_forward` secrets with each buyer's sale and the `top`
+    /// histories overall. `None` if no buyer has four price changes.
+    pub fn sequence_report(&self, look_forward: u64, top: usize) -> Option<SequenceReport> {
+        let totals = self.history_totals(look_forward);
+        let (best, total) = totals.best()?;
+
+        Some(SequenceReport {
+            best,
+            total,
+            sales: self
+                .buyers
+                .iter()
+                .map(|&seed| first_sale(seed, best, look_forward))
+                .collect(),
+            top: totals.top(top),
+        })
+    }
+}
+
+impl FromStr for Market {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Market {
+            buyers: parse::lines(s)
+                .map(|line| line.parse())
+                .collect::<Result<_, _>>()?,
+        })
+    }
 }
 
 pub struct MonkeyMarket;
 
 impl Solution for MonkeyMarket {
-    fn part1(puzzle_input: String) -> String {
-        let secret_nums = puzzle_input.lines().map(str::parse).map(Result::unwrap);
+    fn part1(puzzle_input: String) -> Answer {
+        let market: Market = puzzle_input.parse().unwrap();
 
-        secret_nums
-            .map(|secret| nth_secret(secret, 2000))
-            .sum::<u64>()
-            .to_string()
+        market.secret_sum(2000).into()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let secret_nums: Vec<u64> = puzzle_input
-            .lines()
-            .map(str::parse)
-            .map(Result::unwrap)
-            .collect();
+    fn part2(puzzle_input: String) -> Answer {
+        let market: Market = puzzle_input.parse().unwrap();
 
-        let mut data = HashMap::new();
-        for seed in secret_nums {
-            analyze_nums(seed, 2000, &mut data);
+        match market.sequence_report(2000, TOP_SEQUENCES) {
+            Some(report) => Answer::from(report.total).with_explanation(report.to_string()),
+            None => 0.into(),
         }
-
-        data.values().max().unwrap().to_string()
     }
 }
 
@@ -103,20 +444,147 @@ mod test {
         assert_eq!(secret, 16495136);
     }
 
+    fn analyze(seed: u64, look_forward: u64) -> HistoryTotals {
+        let mut totals = HistoryTotals::new();
+        let mut seen = SeenHistories::new();
+        analyze_nums(seed, look_forward, &mut totals, &mut seen);
+        totals
+    }
+
+    #[test]
+    fn streams_secrets() {
+        let secrets: Vec<_> = SecretStream::new(123).take(4).collect();
+        assert_eq!(secrets, vec![123, 15887950, 16495136, 527345]);
+
+        let prices: Vec<_> = SecretStream::new(123).prices().take(10).collect();
+        assert_eq!(prices, vec![3, 0, 6, 5, 4, 4, 6, 4, 4, 2]);
+
+        assert_eq!(SecretStream::new(1).nth(2000), Some(nth_secret(1, 2000)));
+    }
+
+    #[test]
+    fn steps_backwards() {
+        for secret in [0, 1, 123, 15887950, MAX_SECRET_SIZE - 1] {
+            assert_eq!(previous_secret(step_number(secret)), secret);
+        }
+        assert_eq!(previous_secret(15887950), 123);
+
+        // Which seed has this as its 2000th secret
+        assert_eq!(nth_previous_secret(8685429, 2000), 1);
+        // Seeds past 24 bits step the same as their low bits
+        assert_eq!(previous_secret(step_number(MAX_SECRET_SIZE + 5)), 5);
+    }
+
+    #[test]
+    fn finds_cycles() {
+        assert_eq!(cycle_length(0), 1);
+        assert_eq!(cycle_length(123), MAX_SECRET_SIZE - 1);
+        assert_eq!(cycle_length(MAX_SECRET_SIZE + 123), MAX_SECRET_SIZE - 1);
+    }
+
     #[test]
     fn test_analysis_1() {
-        let mut data = HashMap::new();
-        analyze_nums(123, 10, &mut data);
-        assert_eq!(data.get(&(-1, -1, 0, 2)), Some(&6));
-        assert_eq!(data.get(&(2, -2, 0, -2)).cloned(), Some(2));
+        let data = analyze(123, 10);
+        assert_eq!(data.get((-1, -1, 0, 2)), 6);
+        assert_eq!(data.get((2, -2, 0, -2)), 2);
+        // 10 secrets give 7 histories, one for each price from the fourth change on
+        assert_eq!(data.iter().count(), 7);
     }
 
     #[test]
     fn test_analysis_2() {
-        let mut data = HashMap::new();
-        analyze_nums(1, 2000, &mut data);
-        dbg!(&data);
-        assert_eq!(data.get(&(-2, 1, -1, 3)).cloned(), Some(7));
+        let data = analyze(1, 2000);
+        assert_eq!(data.get((-2, 1, -1, 3)), 7);
+    }
+
+    #[test]
+    fn indexes_histories() {
+        for history in [(-9, -9, -9, -9), (0, 0, 0, 0), (9, 9, 9, 9), (-2, 1, -1, 3)] {
+            assert_eq!(index_history(history_index(history)), history);
+        }
+        assert_eq!(history_index((9, 9, 9, 9)), HISTORIES - 1);
+    }
+
+    #[test]
+    fn sums_buyers() {
+        let market: Market = "1\n2\n3\n2024".parse().unwrap();
+        let totals = market.history_totals(2000);
+        assert_eq!(totals.best(), Some(((-2, 1, -1, 3), 23)));
+
+        // Same as adding the buyers up one at a time
+        let mut seen = SeenHistories::new();
+        let mut serial = HistoryTotals::new();
+        for &seed in &market.buyers {
+            analyze_nums(seed, 2000, &mut serial, &mut seen);
+        }
+        assert_eq!(serial, totals);
+    }
+
+    #[test]
+    fn forgets_histories_between_buyers() {
+        let mut seen = SeenHistories::new();
+        assert!(seen.insert(7));
+        assert!(!seen.insert(7));
+        seen.next_buyer();
+        assert!(seen.insert(7));
+
+        // A stamp left over from before the generation wrapped doesn't count
+        seen.generation = u32::MAX;
+        seen.stamps[8] = 1;
+        seen.next_buyer();
+        assert!(seen.insert(8));
+        assert!(!seen.insert(8));
+    }
+
+    #[test]
+    fn parses_market() {
+        let market: Market = "1\n10\n100\n2024".parse().unwrap();
+        assert_eq!(market.buyers, vec![1, 10, 100, 2024]);
+        assert_eq!(market.secret_sum(2000), 37327623);
+
+        assert_eq!("1\n-2".parse::<Market>().unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn reports_best_sequence() {
+        let market = Market {
+            buyers: vec![1, 2, 3, 2024],
+        };
+        let report = market.sequence_report(2000, 3).unwrap();
+
+        assert_eq!(report.best, (-2, 1, -1, 3));
+        assert_eq!(report.total, 23);
+        let prices: Vec<_> = report
+            .sales
+            .iter()
+            .map(|sale| sale.map(|sale| sale.price))
+            .collect();
+        assert_eq!(prices, vec![Some(7), Some(7), None, Some(9)]);
+        assert_eq!(report.top.len(), 3);
+        assert_eq!(report.top[0], (report.best, 23));
+        assert!(report.top[1].1 <= 23);
+
+        // Each sale is at the end of the winning changes
+        for (&seed, sale) in market.buyers.iter().zip(&report.sales) {
+            let Some(sale) = sale else { continue };
+            let prices: Vec<_> = SecretStream::new(seed)
+                .prices()
+                .skip(sale.index as usize - 4)
+                .take(5)
+                .map(i8::try_from)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            let changes: Vec<_> = prices.windows(2).map(|w| w[1] - w[0]).collect();
+            assert_eq!(changes, vec![-2, 1, -1, 3]);
+            assert_eq!(prices[4], sale.price as i8);
+        }
+
+        assert_eq!(
+            first_sale(123, (-1, -1, 0, 2), 10),
+            Some(Sale { index: 6, price: 6 })
+        );
+        assert_eq!(first_sale(123, (-1, -1, 0, 2), 5), None);
+        assert_eq!(Market { buyers: vec![] }.sequence_report(2000, 3), None);
     }
 
     #[test]
@@ -1,18 +1,62 @@
-use crate::solution::Solution;
+//! Day 2: Red-Nosed Reports
+
+use std::str::FromStr;
+
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError, ParseResult},
+    solution::Solution,
+};
 
 pub struct MonotonicReport;
 
-struct Interval {
-    start: i32,
-    end: i32,
+/// A single reactor report, one line of levels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub levels: Vec<i32>,
+}
+
+impl Report {
+    /// Whether the levels move in one direction by at most `tolerance` each step
+    pub fn is_safe(&self, tolerance: i32) -> bool {
+        MonotonicReport::is_monotonic(&self.levels, tolerance)
+    }
+
+    /// Like [`Report::is_safe`] but a single bad level may be removed
+    pub fn is_safe_with_fault(&self, tolerance: i32) -> bool {
+        MonotonicReport::is_monotonic_with_fault(&self.levels, tolerance)
+    }
+}
+
+impl FromStr for Report {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let levels = s
+            .split_whitespace()
+            .map(|level| {
+                level
+                    .parse()
+                    .map_err(|e| ParseError::new(1, format!("invalid level '{level}': {e}")))
+            })
+            .collect::<ParseResult<_>>()?;
+
+        Ok(Report { levels })
+    }
+}
+
+/// The change between two neighbouring levels
+pub struct Interval {
+    pub start: i32,
+    pub end: i32,
 }
 
 impl Interval {
-    fn new(start: i32, end: i32) -> Interval {
+    pub fn new(start: i32, end: i32) -> Interval {
         Interval { start, end }
     }
 
-    fn direction(&self) -> Direction {
+    pub fn direction(&self) -> Direction {
         match self.end - self.start {
             x if x > 0 => Direction::Increasing,
             x if x < 0 => Direction::Decreasing,
@@ -20,24 +64,25 @@ impl Interval {
         }
     }
 
-    fn is_monotonic(&self, direction: &Direction) -> bool {
+    pub fn is_monotonic(&self, direction: &Direction) -> bool {
         self.direction() == *direction
     }
 
-    fn is_within_tolerance(&self, tolerance: i32) -> bool {
+    pub fn is_within_tolerance(&self, tolerance: i32) -> bool {
         (self.start - self.end).abs() <= tolerance
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
-enum Direction {
+pub enum Direction {
     Increasing,
     Decreasing,
     Neither,
 }
 
 impl MonotonicReport {
-    fn list_direction(nums: &[i32]) -> Direction {
+    /// The direction most of the steps in `nums` go in
+    pub fn list_direction(nums: &[i32]) -> Direction {
         let mut increasing = 0;
         let mut decreasing = 0;
 
@@ -56,15 +101,14 @@ impl MonotonicReport {
         }
     }
 
-    fn is_monotonic(nums: &[i32], tolerance: i32) -> bool {
+    pub fn is_monotonic(nums: &[i32], tolerance: i32) -> bool {
         let list_dir = MonotonicReport::list_direction(nums);
-        return nums
-            .windows(2)
+        nums.windows(2)
             .map(|window| Interval::new(window[0], window[1]))
-            .all(|i| i.is_monotonic(&list_dir) && i.is_within_tolerance(tolerance));
+            .all(|i| i.is_monotonic(&list_dir) && i.is_within_tolerance(tolerance))
     }
 
-    fn is_monotonic_with_fault(nums: &[i32], tolerance: i32) -> bool {
+    pub fn is_monotonic_with_fault(nums: &[i32], tolerance: i32) -> bool {
         if MonotonicReport::is_monotonic(nums, tolerance) {
             return true;
         }
@@ -85,43 +129,48 @@ impl MonotonicReport {
         false
     }
 
-    fn parse_input(puzzle_input: String) -> Vec<Vec<i32>> {
-        let lines: Result<Vec<_>, _> = puzzle_input
-            .lines()
-            .map(|l| {
-                let report: Result<Vec<_>, _> =
-                    l.split_whitespace().map(str::parse::<i32>).collect();
-
-                report
-            })
-            .collect();
-
-        lines.expect("Lines should contain valid i32 space-separated numbers.")
+    pub fn parse_input(puzzle_input: &str) -> ParseResult<Vec<Report>> {
+        parse::parse_lines(puzzle_input)
     }
 }
 
 impl Solution for MonotonicReport {
-    fn part1(puzzle_input: String) -> String {
-        MonotonicReport::parse_input(puzzle_input)
+    fn part1(puzzle_input: String) -> Answer {
+        MonotonicReport::parse_input(&puzzle_input)
+            .unwrap()
             .iter()
-            .filter_map(|r| MonotonicReport::is_monotonic(r, 3).then_some(1))
+            .filter_map(|r| r.is_safe(3).then_some(1))
             .count()
-            .to_string()
+            .into()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        MonotonicReport::parse_input(puzzle_input)
+    fn part2(puzzle_input: String) -> Answer {
+        MonotonicReport::parse_input(&puzzle_input)
+            .unwrap()
             .iter()
-            .filter_map(|r| MonotonicReport::is_monotonic_with_fault(r, 3).then_some(1))
+            .filter_map(|r| r.is_safe_with_fault(3).then_some(1))
             .count()
-            .to_string()
+            .into()
     }
 }
 
 #[cfg(test)]
+// The tests predate passing slices around and build their reports with vec!
+#[allow(clippy::useless_vec)]
 mod test {
     use super::*;
 
+    #[test]
+    fn parses_reports() {
+        let reports = MonotonicReport::parse_input("7 6 4 2 1\n1 2 7 8 9\n9 7 x").unwrap_err();
+        assert_eq!(reports.line, 3);
+
+        let report: Report = "1 3 6 7 9".parse().unwrap();
+        assert_eq!(report.levels, vec![1, 3, 6, 7, 9]);
+        assert!(report.is_safe(3));
+        assert!(!"1 2 7 8 9".parse::<Report>().unwrap().is_safe_with_fault(3));
+    }
+
     // Basic monotonic tests
     #[test]
     fn detects_increasing() {
@@ -1,9 +1,14 @@
+//! Day 3: Mull It Over
+
+use std::str::FromStr;
+
 use regex::{Captures, Regex};
 
-use crate::solution::Solution;
+use crate::{answer::Answer, parse::ParseError, solution::Solution};
 
-#[derive(Debug)]
-enum Op {
+/// An instruction recovered from the corrupted memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
     Mul(i64, i64),
     Do,
     Dont,
@@ -22,39 +27,93 @@ impl Op {
     }
 }
 
-pub struct MullSolution;
-
-impl MullSolution {}
-
-impl Solution for MullSolution {
-    fn part1(puzzle_input: String) -> String {
-        let re = Regex::new(r"mul\((?<arg1>\d{1,3}),(?<arg2>\d{1,3})\)").unwrap();
+/// The valid instructions in a corrupted memory dump, in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memory {
+    pub ops: Vec<Op>,
+}
 
-        return re
-            .captures_iter(&puzzle_input)
-            .map(|c| {
-                c["arg1"].parse::<i64>().unwrap() * c["arg2"].parse::<i64>().unwrap()
+impl Memory {
+    /// Sum of every `mul`, ignoring `do()`/`don't()`
+    pub fn sum_products(&self) -> i64 {
+        self.ops
+            .iter()
+            .map(|op| match op {
+                Op::Mul(a, b) => a * b,
+                _ => 0,
             })
-            .sum::<i64>()
-            .to_string();
+            .sum()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let re = Regex::new(r"mul\((?<arg1>\d{1,3}),(?<arg2>\d{1,3})\)|do\(\)|don't\(\)").unwrap();
-
-        return re
-            .captures_iter(&puzzle_input)
-            .map(Op::new_from_capture)
+    /// Sum of the `mul`s which are enabled by the latest `do()`/`don't()`
+    pub fn sum_enabled_products(&self) -> i64 {
+        self.ops
+            .iter()
             .fold((true, 0), |acc, op| {
                 let (is_enabled, total) = acc;
                 match op {
                     Op::Do if !is_enabled => (true, total),
                     Op::Dont if is_enabled => (false, total),
                     Op::Mul(a, b) if is_enabled => (true, total + a * b),
-                    _ => (is_enabled, total)
+                    _ => (is_enabled, total),
                 }
             })
             .1
-            .to_string();
+    }
+}
+
+impl FromStr for Memory {
+    type Err = ParseError;
+
+    /// Corrupted memory can't fail to parse, anything which isn't an instruction is skipped
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let re = Regex::new(r"mul\((?<arg1>\d{1,3}),(?<arg2>\d{1,3})\)|do\(\)|don't\(\)").unwrap();
+
+        Ok(Memory {
+            ops: re.captures_iter(s).map(Op::new_from_capture).collect(),
+        })
+    }
+}
+
+pub struct MullSolution;
+
+impl Solution for MullSolution {
+    fn part1(puzzle_input: String) -> Answer {
+        let memory: Memory = puzzle_input.parse().unwrap();
+
+        memory.sum_products().into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let memory: Memory = puzzle_input.parse().unwrap();
+
+        memory.sum_enabled_products().into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_instructions() {
+        let memory: Memory =
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))"
+                .parse()
+                .unwrap();
+
+        assert_eq!(
+            memory.ops,
+            vec![
+                Op::Mul(2, 4),
+                Op::Dont,
+                Op::Mul(5, 5),
+                Op::Mul(11, 8),
+                Op::Do,
+                Op::Mul(8, 5)
+            ]
+        );
+        assert_eq!(memory.sum_products(), 161);
+        assert_eq!(memory.sum_enabled_products(), 48);
     }
 }
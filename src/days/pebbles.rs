@@ -1,10 +1,54 @@
-use std::collections::HashMap;
+//! Day 11: Plutonian Pebbles
 
-use crate::solution::Solution;
+use std::str::FromStr;
+
+use crate::{
+    answer::Answer,
+    memo::{Memo, MemoStats},
+    parse::{self, ParseError},
+    solution::Solution,
+};
 
 pub struct PebbleCounterSolution;
 
-fn blink_pebble(pebble: u64) -> (u64, Option<u64>) {
+/// The engraved numbers on the line of stones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stones {
+    pub numbers: Vec<u64>,
+}
+
+impl Stones {
+    /// Number of stones after blinking `blinks` times
+    pub fn count_after(&self, blinks: u64) -> u64 {
+        self.count_with_stats(blinks).0
+    }
+
+    /// [`Stones::count_after`] along with how well the memo did
+    pub fn count_with_stats(&self, blinks: u64) -> (u64, MemoStats) {
+        let mut memtable = Memo::new();
+
+        let count = self
+            .numbers
+            .iter()
+            .map(|n| count_pebbles_memoized(*n, blinks, &mut memtable))
+            .sum::<u64>();
+
+        (count, memtable.stats())
+    }
+}
+
+impl FromStr for Stones {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Stones {
+            numbers: parse::ints(s)?,
+        })
+    }
+}
+
+/// What a single stone turns into after one blink
+pub fn blink_pebble(pebble: u64) -> (u64, Option<u64>) {
     match pebble {
         0 => (1, None),
         x if (x.ilog10() + 1) % 2 == 0 => {
@@ -20,11 +64,11 @@ fn blink_pebble(pebble: u64) -> (u64, Option<u64>) {
     }
 }
 
-/// Returns the number of pubbles after a certain number of blinks
-fn count_pebbles_memoized(
+/// Returns the number of pebbles after a certain number of blinks
+pub fn count_pebbles_memoized(
     pebble: u64,
     blinks: u64,
-    num_pebbles: &mut HashMap<(u64, u64), u64>,
+    num_pebbles: &mut Memo<(u64, u64), u64>,
 ) -> u64 {
     // Base cases
     if blinks == 0 {
@@ -32,50 +76,58 @@ fn count_pebbles_memoized(
         return 1;
     }
 
-    // Lookup memoized value
-    if let Some(x) = num_pebbles.get(&(pebble, blinks)) {
-        return *x;
+    // Lookup the memoized value or compute it
+    num_pebbles.get_or_compute((pebble, blinks), |num_pebbles| {
+        let (lhs, rhs) = blink_pebble(pebble);
+        count_pebbles_memoized(lhs, blinks - 1, num_pebbles)
+            + rhs.map_or(0, |rhs| {
+                count_pebbles_memoized(rhs, blinks - 1, num_pebbles)
+            })
+    })
+}
+
+impl Solution for PebbleCounterSolution {
+    fn part1(puzzle_input: String) -> Answer {
+        let stones: Stones = puzzle_input.parse().unwrap();
+
+        let (count, stats) = stones.count_with_stats(25);
+        Answer::from(count).with_explanation(format!("memo: {stats}"))
     }
 
-    // Compute memoized value
-    let (lhs, rhs) = blink_pebble(pebble);
-    let result = count_pebbles_memoized(lhs, blinks - 1, num_pebbles)
-        + rhs.map_or(0, |rhs| {
-            count_pebbles_memoized(rhs, blinks - 1, num_pebbles)
-        });
+    fn part2(puzzle_input: String) -> Answer {
+        let stones: Stones = puzzle_input.parse().unwrap();
 
-    // Memoize and return
-    num_pebbles.insert((pebble, blinks), result);
-    result
+        let (count, stats) = stones.count_with_stats(75);
+        Answer::from(count).with_explanation(format!("memo: {stats}"))
+    }
 }
 
-impl Solution for PebbleCounterSolution {
-    fn part1(puzzle_input: String) -> String {
-        let nums: Vec<u64> = puzzle_input
-            .split_whitespace()
-            .map(str::parse)
-            .map(Result::unwrap)
-            .collect();
-
-        let mut memtable = HashMap::new();
-
-        nums.iter()
-            .map(|n| count_pebbles_memoized(*n, 25, &mut memtable))
-            .sum::<u64>()
-            .to_string()
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn blinks_stones() {
+        assert_eq!(blink_pebble(0), (1, None));
+        assert_eq!(blink_pebble(1000), (10, Some(0)));
+        assert_eq!(blink_pebble(1), (2024, None));
+    }
+
+    #[test]
+    fn example() {
+        let stones: Stones = "125 17\n".parse().unwrap();
+        assert_eq!(stones.numbers, vec![125, 17]);
+        assert_eq!(stones.count_after(6), 22);
+        assert_eq!(stones.count_after(25), 55312);
     }
-    fn part2(puzzle_input: String) -> String {
-        let nums: Vec<u64> = puzzle_input
-            .split_whitespace()
-            .map(str::parse)
-            .map(Result::unwrap)
-            .collect();
-
-        let mut memtable = HashMap::new();
-
-        nums.iter()
-            .map(|n| count_pebbles_memoized(*n, 75, &mut memtable))
-            .sum::<u64>()
-            .to_string()
+
+    #[test]
+    fn memo_is_reused() {
+        let mut memo = Memo::new();
+        count_pebbles_memoized(125, 25, &mut memo);
+
+        let stats = memo.stats();
+        assert!(stats.hits > 0);
+        assert_eq!(stats.size as u64, stats.misses);
     }
 }
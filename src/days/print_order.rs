@@ -1,17 +1,25 @@
-use std::collections::HashMap;
+//! Day 5: Print Queue
 
-use crate::solution::Solution;
+use std::{collections::HashMap, str::FromStr};
 
-type Edge = (u32, u32);
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError, ParseResult},
+    solution::Solution,
+};
 
+/// An ordering rule `a|b`: page `a` must be printed before page `b`
+pub type Edge = (u32, u32);
+
+/// Ordering rules as a directed graph over page numbers
 #[derive(Debug)]
-struct Graph {
-    forward_edges: HashMap<u32, Vec<u32>>,
-    backward_edges: HashMap<u32, Vec<u32>>,
+pub struct Graph {
+    pub forward_edges: HashMap<u32, Vec<u32>>,
+    pub backward_edges: HashMap<u32, Vec<u32>>,
 }
 
 impl Graph {
-    fn build(nodes: Vec<u32>, edges: &[Edge]) -> Graph {
+    pub fn build(nodes: Vec<u32>, edges: &[Edge]) -> Graph {
         let mut forward_edges = HashMap::new();
         let mut backward_edges = HashMap::new();
 
@@ -37,7 +45,8 @@ impl Graph {
         }
     }
 
-    fn roots(&self) -> Vec<u32> {
+    /// Nodes without any incoming edges
+    pub fn roots(&self) -> Vec<u32> {
         self.backward_edges
             .iter()
             .filter_map(|(k, v)| v.is_empty().then_some(k))
@@ -45,11 +54,11 @@ impl Graph {
             .collect()
     }
 
-    fn afters(&self, num: u32) -> Option<Vec<u32>> {
+    pub fn afters(&self, num: u32) -> Option<Vec<u32>> {
         self.forward_edges.get(&num).cloned()
     }
 
-    fn remove_edge(&mut self, edge: Edge) {
+    pub fn remove_edge(&mut self, edge: Edge) {
         // Remove forward edge
         let f = self.forward_edges.get_mut(&edge.0).unwrap();
         let f_index = f.iter().position(|&n| n == edge.1).unwrap();
@@ -63,37 +72,44 @@ impl Graph {
 }
 
 fn build_num_to_index(list: &[u32]) -> HashMap<u32, usize> {
-    list.iter()
-        .enumerate()
-        .map(|(i, n)| (n.clone(), i))
-        .collect()
+    list.iter().enumerate().map(|(i, n)| (*n, i)).collect()
 }
 
-pub struct PrintOrder;
+/// The page ordering rules and the updates to print
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyManual {
+    pub rules: Vec<Edge>,
+    pub updates: Vec<Vec<u32>>,
+}
 
-impl PrintOrder {
-    fn parse_input(puzzle_input: String) -> (Vec<Edge>, Vec<Vec<u32>>) {
-        let mut parts = puzzle_input.split("\n\n");
+impl FromStr for SafetyManual {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [rules, updates] = parse::sections_n(s)?;
 
-        let constraints = parts
-            .next()
-            .expect("Should have a constraints part")
+        let rules = rules
             .lines()
-            .map(|l| l.split_once('|').unwrap())
-            .map(|(a, b)| (a.parse().unwrap(), b.parse().unwrap()))
-            .collect();
+            .map(|line| {
+                let (a, b) = line.split_once("|")?;
+                Ok((a.parse()?, b.parse()?))
+            })
+            .collect::<ParseResult<_>>()?;
 
-        let lists: Vec<Vec<_>> = parts
-            .next()
-            .expect("Should have a lists part")
+        let updates = updates
             .lines()
-            .map(|l| l.split(',').map(|n| n.parse::<u32>().unwrap()).collect())
-            .collect();
+            .map(|line| line.ints())
+            .collect::<ParseResult<_>>()?;
 
-        (constraints, lists)
+        Ok(SafetyManual { rules, updates })
     }
+}
 
-    fn is_in_order(list: &[u32], constraints: &[Edge]) -> bool {
+pub struct PrintOrder;
+
+impl PrintOrder {
+    /// Whether `list` satisfies every rule which mentions two of its pages
+    pub fn is_in_order(list: &[u32], constraints: &[Edge]) -> bool {
         let num_to_index: HashMap<u32, usize> = build_num_to_index(list);
 
         for constr in constraints {
@@ -107,7 +123,7 @@ impl PrintOrder {
     }
 
     /// Use a topological sort to build the correct ordering of the numbers
-    fn fix_order(list: &[u32], constraints: &[Edge]) -> Vec<u32> {
+    pub fn fix_order(list: &[u32], constraints: &[Edge]) -> Vec<u32> {
         let edges: Vec<_> = constraints
             .iter()
             .filter(|c| list.contains(&c.0) && list.contains(&c.1))
@@ -118,8 +134,7 @@ impl PrintOrder {
         let mut graph = Graph::build(list.to_vec(), &edges);
         let mut roots = graph.roots();
 
-        while !roots.is_empty() {
-            let node = roots.pop().unwrap();
+        while let Some(node) = roots.pop() {
             output.push(node);
             for m in graph.afters(node).unwrap() {
                 graph.remove_edge((node, m));
@@ -129,31 +144,96 @@ impl PrintOrder {
             }
         }
 
-        return output;
+        output
     }
 }
 
 impl Solution for PrintOrder {
-    fn part1(puzzle_input: String) -> String {
-        let (constraints, lists) = PrintOrder::parse_input(puzzle_input);
+    fn part1(puzzle_input: String) -> Answer {
+        let SafetyManual {
+            rules: constraints,
+            updates: lists,
+        } = puzzle_input.parse().unwrap();
 
         lists
             .iter()
             .filter(|l| PrintOrder::is_in_order(l, &constraints))
             .map(|l| l.get(l.len() / 2).unwrap())
             .sum::<u32>()
-            .to_string()
+            .into()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let (constraints, mut lists) = PrintOrder::parse_input(puzzle_input);
+    fn part2(puzzle_input: String) -> Answer {
+        let SafetyManual {
+            rules: constraints,
+            updates: mut lists,
+        } = puzzle_input.parse().unwrap();
 
         lists
             .iter_mut()
             .filter(|l| !PrintOrder::is_in_order(l, &constraints))
             .map(|l| PrintOrder::fix_order(l, &constraints))
-            .map(|l| l.get(l.len() / 2).unwrap().clone())
+            .map(|l| *l.get(l.len() / 2).unwrap())
             .sum::<u32>()
-            .to_string()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47";
+
+    #[test]
+    fn parses_manual() {
+        let manual: SafetyManual = EXAMPLE.parse().unwrap();
+        assert_eq!(manual.rules.len(), 21);
+        assert_eq!(manual.rules[0], (47, 53));
+        assert_eq!(manual.updates[2], vec![75, 29, 13]);
+
+        assert_eq!(
+            "1|2\n3-4\n\n1,2".parse::<SafetyManual>().unwrap_err().line,
+            2
+        );
+    }
+
+    #[test]
+    fn orders_updates() {
+        let manual: SafetyManual = EXAMPLE.parse().unwrap();
+
+        assert!(PrintOrder::is_in_order(&manual.updates[0], &manual.rules));
+        assert!(!PrintOrder::is_in_order(&manual.updates[3], &manual.rules));
+        assert_eq!(
+            PrintOrder::fix_order(&manual.updates[3], &manual.rules),
+            vec![97, 75, 47, 61, 53]
+        );
     }
 }
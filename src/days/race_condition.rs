@@ -1,15 +1,20 @@
+//! Day 20: Race Condition
+
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     hash::Hash,
     ops::Add,
+    str::FromStr,
 };
 
-use itertools::Itertools;
-
-use crate::solution::Solution;
+use crate::{
+    answer::Answer,
+    parse::{ParseError, Section},
+    solution::Solution,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Point(i32, i32);
+pub struct Point(pub i32, pub i32);
 
 impl Add for Point {
     type Output = Point;
@@ -19,13 +24,13 @@ impl Add for Point {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum MapTile {
+pub enum MapTile {
     Wall,
     Space,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
-enum Direction {
+pub enum Direction {
     North,
     South,
     East,
@@ -33,7 +38,7 @@ enum Direction {
 }
 
 impl Direction {
-    fn as_point(&self) -> Point {
+    pub fn as_point(&self) -> Point {
         match self {
             Direction::North => Point(0, -1),
             Direction::South => Point(0, 1),
@@ -54,39 +59,55 @@ const DIRS: [Direction; 4] = [
 /// `node.0`: the position in the map
 /// `node.1`: the number of wall tiles we are allowed to move through
 /// `node.2`: the number of steps taken to get to this point
+#[allow(dead_code)]
 type Node = (Point, u32, u32);
 
-fn parse_input(puzzle_input: String) -> (HashMap<Point, MapTile>, Point, Point) {
-    let mut hm = HashMap::new();
-    let mut start = None;
-    let mut end = None;
-    for (y, row) in puzzle_input.lines().enumerate() {
-        for (x, cell) in row.chars().enumerate() {
-            let cell_pos = Point(x as i32, y as i32);
-            hm.insert(
-                cell_pos,
-                match cell {
+/// The racetrack map with its start and end
+#[derive(Debug, Clone)]
+pub struct Racetrack {
+    pub map: HashMap<Point, MapTile>,
+    pub start: Point,
+    pub end: Point,
+}
+
+impl FromStr for Racetrack {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let section = Section::new(s);
+        let grid = section.grid(|cell| match cell {
+            '#' | '.' | 'S' | 'E' => Some(cell),
+            _ => None,
+        })?;
+
+        let mut map = HashMap::new();
+        let mut start = None;
+        let mut end = None;
+        for (y, row) in grid.into_iter().enumerate() {
+            for (x, cell) in row.into_iter().enumerate() {
+                let cell_pos = Point(x as i32, y as i32);
+                let tile = match cell {
                     '#' => MapTile::Wall,
                     'S' => {
                         start = Some(cell_pos);
                         MapTile::Space
                     }
-                    '.' => MapTile::Space,
                     'E' => {
                         end = Some(cell_pos);
                         MapTile::Space
                     }
-                    x => panic!("Unknown map element {x}"),
-                },
-            );
+                    _ => MapTile::Space,
+                };
+                map.insert(cell_pos, tile);
+            }
         }
-    }
 
-    (
-        hm,
-        start.expect("Map should have starting point"),
-        end.expect("map should have an ending point"),
-    )
+        Ok(Racetrack {
+            map,
+            start: start.ok_or_else(|| section.error("map should have a starting point"))?,
+            end: end.ok_or_else(|| section.error("map should have an ending point"))?,
+        })
+    }
 }
 
 fn neighbors(node: &Point, map: &HashMap<Point, MapTile>) -> Vec<Point> {
@@ -96,27 +117,27 @@ fn neighbors(node: &Point, map: &HashMap<Point, MapTile>) -> Vec<Point> {
         let neighbor = *node + dir.as_point();
         let tile = map.get(&neighbor).unwrap();
         // cheats timer should run regardless of if we are going through a wall or not
-        match tile {
-            MapTile::Space => neighbors.push(neighbor),
-            _ => {}
+        if tile == &MapTile::Space {
+            neighbors.push(neighbor)
         }
     }
 
     neighbors
 }
 
-fn find_min_dists(map: &HashMap<Point, MapTile>, start: Point) -> HashMap<Point, u64> {
+/// Steps from `start` to every reachable space without cheating
+pub fn find_min_dists(map: &HashMap<Point, MapTile>, start: Point) -> HashMap<Point, u64> {
     let mut min_dists: HashMap<Point, u64> = HashMap::new();
     let mut queue = VecDeque::new();
     queue.push_back((start, 0));
     while let Some((node, dist)) = queue.pop_front() {
-        if min_dists.contains_key(&node) {
-            continue;
+        if let Entry::Vacant(e) = min_dists.entry(node) {
+            e.insert(dist);
         } else {
-            min_dists.insert(node, dist);
+            continue;
         }
 
-        for neighbor in neighbors(&node, &map) {
+        for neighbor in neighbors(&node, map) {
             queue.push_back((neighbor, dist + 1));
         }
     }
@@ -124,8 +145,10 @@ fn find_min_dists(map: &HashMap<Point, MapTile>, start: Point) -> HashMap<Point,
     min_dists
 }
 
-type Cheat = (Point, Point);
+/// Where a cheat starts and ends
+pub type Cheat = (Point, Point);
 
+#[allow(dead_code)]
 fn option_add(lhs: Option<u64>, rhs: Option<u64>) -> Option<u64> {
     match (lhs, rhs) {
         (Some(l), Some(r)) => Some(l + r),
@@ -133,15 +156,14 @@ fn option_add(lhs: Option<u64>, rhs: Option<u64>) -> Option<u64> {
     }
 }
 
-pub struct RaceCondition;
-
-impl Solution for RaceCondition {
-    fn part1(puzzle_input: String) -> String {
-        let (map, start, end) = parse_input(puzzle_input);
-        let min_start_dists = find_min_dists(&map, start);
-        let min_end_dists = find_min_dists(&map, end);
+impl Racetrack {
+    /// Every two step cheat through a single wall and how many steps it saves
+    pub fn wall_cheats(&self) -> HashMap<Cheat, u64> {
+        let map = &self.map;
+        let min_start_dists = find_min_dists(map, self.start);
+        let min_end_dists = find_min_dists(map, self.end);
 
-        let dist_to_beat = min_start_dists.get(&end).unwrap();
+        let dist_to_beat = min_start_dists.get(&self.end).unwrap();
 
         let mut cheat_legend: HashMap<Cheat, u64> = HashMap::new();
 
@@ -157,10 +179,7 @@ impl Solution for RaceCondition {
                 .iter()
                 .filter_map(|start_dir| {
                     let cheat_start = *wall_pos + start_dir.as_point();
-                    match min_start_dists.get(&cheat_start) {
-                        Some(dist) => Some(dist + 1),
-                        None => None,
-                    }
+                    min_start_dists.get(&cheat_start).map(|dist| dist + 1)
                 })
                 .min()
             {
@@ -185,18 +204,15 @@ impl Solution for RaceCondition {
         }
 
         cheat_legend
-            .values()
-            .filter(|d| **d >= 100)
-            .count()
-            .to_string()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let (map, start, end) = parse_input(puzzle_input);
-        let min_start_dists = find_min_dists(&map, start);
-        let min_end_dists = find_min_dists(&map, end);
+    /// Every cheat lasting up to `max_length` steps and how many steps it saves
+    pub fn cheats(&self, max_length: u64) -> HashMap<Cheat, u64> {
+        let map = &self.map;
+        let min_start_dists = find_min_dists(map, self.start);
+        let min_end_dists = find_min_dists(map, self.end);
 
-        let dist_to_beat = min_start_dists.get(&end).unwrap();
+        let dist_to_beat = min_start_dists.get(&self.end).unwrap();
 
         let mut cheat_legend: HashMap<Cheat, u64> = HashMap::new();
 
@@ -208,7 +224,7 @@ impl Solution for RaceCondition {
         // Look for cheats
         for space_pos in spaces {
             // Lookup the shortest way to get to this space
-            let start_dist = match min_start_dists.get(&space_pos) {
+            let start_dist = match min_start_dists.get(space_pos) {
                 Some(d) => d,
                 None => continue,
             };
@@ -239,26 +255,95 @@ impl Solution for RaceCondition {
                         cheat_legend.insert((*space_pos, position), *dist_to_beat - cheat_dist);
                     }
                 }
-                
-                if dist > 19 {
+
+                if dist >= max_length {
                     continue;
                 }
 
                 // Neighborhood exploration
                 for neighbor_dir in DIRS {
                     let neighbor = position + neighbor_dir.as_point();
-                    if let Some(_) = map.get(&neighbor) {
+                    if map.contains_key(&neighbor) {
                         frontier.push_back((neighbor, dist + 1));
                     }
                 }
             }
         }
 
-
         cheat_legend
+    }
+}
+
+pub struct RaceCondition;
+
+impl Solution for RaceCondition {
+    fn part1(puzzle_input: String) -> Answer {
+        let track: Racetrack = puzzle_input.parse().unwrap();
+
+        track
+            .wall_cheats()
+            .values()
+            .filter(|d| **d >= 100)
+            .count()
+            .into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let track: Racetrack = puzzle_input.parse().unwrap();
+
+        track
+            .cheats(20)
             .values()
             .filter(|d| **d >= 100)
             .count()
-            .to_string()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############";
+
+    fn count_saving(cheats: &HashMap<Cheat, u64>, saving: u64) -> usize {
+        cheats.values().filter(|d| **d == saving).count()
+    }
+
+    #[test]
+    fn parses_track() {
+        let track: Racetrack = EXAMPLE.parse().unwrap();
+        assert_eq!(track.start, Point(1, 3));
+        assert_eq!(track.end, Point(5, 7));
+        assert_eq!(find_min_dists(&track.map, track.start)[&track.end], 84);
+
+        assert!("#S.\n#..".parse::<Racetrack>().is_err());
+    }
+
+    #[test]
+    fn example() {
+        let track: Racetrack = EXAMPLE.parse().unwrap();
+
+        let wall_cheats = track.wall_cheats();
+        assert_eq!(count_saving(&wall_cheats, 64), 1);
+        assert_eq!(count_saving(&wall_cheats, 12), 3);
+
+        let cheats = track.cheats(20);
+        assert_eq!(count_saving(&cheats, 76), 3);
+        assert_eq!(count_saving(&cheats, 50), 32);
     }
 }
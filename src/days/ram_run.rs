@@ -1,13 +1,21 @@
+//! Day 18: RAM Run
+
 use std::{
-    collections::{HashSet, VecDeque}, fmt::format, ops::Add
+    collections::{HashSet, VecDeque},
+    ops::Add,
+    str::FromStr,
 };
 
-use crate::solution::Solution;
+use crate::{
+    answer::Answer,
+    parse::{self, Line, ParseError, ParseResult},
+    solution::Solution,
+};
 
 const DIRS: [Point; 4] = [Point(0, 1), Point(1, 0), Point(0, -1), Point(-1, 0)];
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
-struct Point(i32, i32);
+pub struct Point(pub i32, pub i32);
 
 impl Add for Point {
     type Output = Point;
@@ -16,6 +24,17 @@ impl Add for Point {
     }
 }
 
+impl FromStr for Point {
+    type Err = ParseError;
+
+    /// Parses a falling byte written as `x,y`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x, y] = Line::new(s).ints_n()?;
+
+        Ok(Point(x, y))
+    }
+}
+
 fn neighbors(p: Point, size: i32) -> Vec<Point> {
     let mut neighbors = Vec::new();
 
@@ -30,22 +49,12 @@ fn neighbors(p: Point, size: i32) -> Vec<Point> {
     neighbors
 }
 
-fn parse_input(puzzle_input: String) -> Vec<Point> {
-    puzzle_input
-        .lines()
-        .map(|l| {
-            let nums: Vec<_> = l
-                .split(',')
-                .map(str::parse::<i32>)
-                .map(Result::unwrap)
-                .collect();
-
-            Point(nums[0], nums[1])
-        })
-        .collect()
+pub fn parse_input(puzzle_input: &str) -> ParseResult<Vec<Point>> {
+    parse::parse_lines(puzzle_input)
 }
 
-fn shortest_path(barriers: &[Point], size: i32) -> Option<i32> {
+/// Fewest steps from `(0, 0)` to `(size, size)` avoiding `barriers`, if the exit is reachable
+pub fn shortest_path(barriers: &[Point], size: i32) -> Option<i32> {
     let dropped_barriers: HashSet<Point> = barriers.iter().cloned().collect();
 
     let goal = Point(size, size);
@@ -77,11 +86,30 @@ fn shortest_path(barriers: &[Point], size: i32) -> Option<i32> {
     None
 }
 
+/// The first byte which cuts off the exit once it and every byte before it has fallen
+pub fn first_blocking_byte(barriers: &[Point], size: i32) -> Option<Point> {
+    let mut start = 0; // Inclusive
+    let mut end = barriers.len(); // Exclusive
+    while start < end {
+        let mid = (start + end) / 2;
+        // Mid is included in the test
+        let solvable = shortest_path(&barriers[..mid + 1], size);
+
+        if solvable.is_some() {
+            start = mid + 1;
+        } else {
+            end = mid
+        }
+    }
+
+    barriers.get(start).cloned()
+}
+
 pub struct RamRunSolution;
 
 impl Solution for RamRunSolution {
-    fn part1(puzzle_input: String) -> String {
-        let barriers = parse_input(puzzle_input);
+    fn part1(puzzle_input: String) -> Answer {
+        let barriers = parse_input(&puzzle_input).unwrap();
         let (size, num_barriers) = if barriers.len() <= 25 {
             (6, 12)
         } else {
@@ -90,28 +118,60 @@ impl Solution for RamRunSolution {
 
         let solution = shortest_path(&barriers[..num_barriers], size);
 
-        solution.unwrap().to_string()
+        solution.unwrap().into()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let barriers = parse_input(puzzle_input);
+    fn part2(puzzle_input: String) -> Answer {
+        let barriers = parse_input(&puzzle_input).unwrap();
         let size = if barriers.len() <= 25 { 6 } else { 70 };
 
-        let mut start = 0; // Inclusive
-        let mut end = barriers.len(); // Exclusive
-        while start < end {
-            let mid = (start + end) / 2;
-            // Mid is included in the test
-            let solvable = shortest_path(&barriers[..mid + 1], size);
-
-            if solvable.is_some() {
-                start = mid + 1;
-            } else {
-                end = mid
-            }
-        }
+        let breakpoint = first_blocking_byte(&barriers, size).unwrap();
+        Answer::Coord(breakpoint.0 as i64, breakpoint.1 as i64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0";
+
+    #[test]
+    fn parses_bytes() {
+        assert_eq!("6,1".parse::<Point>().unwrap(), Point(6, 1));
+        assert!("6".parse::<Point>().is_err());
+        assert_eq!(parse_input("1,2\n3;4\n5").unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn example() {
+        let barriers = parse_input(EXAMPLE).unwrap();
 
-        let breakpoint = barriers.get(start).unwrap();
-        format!("{},{}", breakpoint.0, breakpoint.1)
+        assert_eq!(shortest_path(&barriers[..12], 6), Some(22));
+        assert_eq!(first_blocking_byte(&barriers, 6), Some(Point(6, 1)));
     }
 }
@@ -1,15 +1,20 @@
-use core::panic;
+//! Day 16: Reindeer Maze
+
 use std::{
     cmp::Reverse,
     collections::{BinaryHeap, HashMap, HashSet},
     ops::Add,
-    u64,
+    str::FromStr,
 };
 
-use crate::solution::Solution;
+use crate::{
+    answer::Answer,
+    parse::{ParseError, Section},
+    solution::Solution,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
-struct Point(i64, i64);
+pub struct Point(pub i64, pub i64);
 
 impl Add for Point {
     type Output = Point;
@@ -19,13 +24,13 @@ impl Add for Point {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum MapTile {
+pub enum MapTile {
     Wall,
     Hall,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, PartialOrd, Ord)]
-enum Direction {
+pub enum Direction {
     North,
     South,
     West,
@@ -40,7 +45,7 @@ const DIRS: [Direction; 4] = [
 ];
 
 impl Direction {
-    fn as_point(&self) -> Point {
+    pub fn as_point(&self) -> Point {
         match self {
             Direction::North => Point(0, -1),
             Direction::South => Point(0, 1),
@@ -49,50 +54,114 @@ impl Direction {
         }
     }
 
-    fn left(&self) -> Direction {
+    pub fn left(&self) -> Direction {
         let current_dir_idx = DIRS.iter().position(|d| *d == *self).unwrap() as isize;
         DIRS[(current_dir_idx - 1).rem_euclid(DIRS.len() as isize) as usize]
     }
 
-    fn right(&self) -> Direction {
+    pub fn right(&self) -> Direction {
         let current_dir_idx = DIRS.iter().position(|d| *d == *self).unwrap() as isize;
         DIRS[(current_dir_idx + 1).rem_euclid(DIRS.len() as isize) as usize]
     }
 }
 
-fn parse_input(puzzle_input: String) -> (HashMap<Point, MapTile>, Point, Point) {
-    let mut hm = HashMap::new();
-    let mut start = None;
-    let mut exit = None;
-    for (y, row) in puzzle_input.lines().enumerate() {
-        for (x, cell) in row.chars().enumerate() {
-            hm.insert(
-                Point(x as i64, y as i64),
+/// The maze along with the start and end tiles, the reindeer starts facing east
+#[derive(Debug, Clone)]
+pub struct Maze {
+    pub map: HashMap<Point, MapTile>,
+    pub start: Point,
+    pub exit: Point,
+}
+
+impl Maze {
+    /// Lowest score any path from the start to the exit can get
+    pub fn best_score(&self) -> u64 {
+        let min_dists = find_min_dists(&self.map, self.start);
+
+        *expand(self.exit)
+            .iter()
+            .map(|n| min_dists.get(n).unwrap())
+            .min()
+            .unwrap()
+    }
+
+    /// Every tile which is part of at least one best path
+    pub fn best_path_tiles(&self) -> HashSet<Point> {
+        let min_dists = find_min_dists(&self.map, self.start);
+        let starting_nodes = expand(self.exit);
+
+        let best_dist = expand(self.exit)
+            .iter()
+            .map(|n| min_dists.get(n).unwrap())
+            .min()
+            .unwrap();
+
+        // Reverse-search through the nodes to find any which could be part of a best path
+        let mut visited = HashSet::new();
+        // Frontier contains nodes, dist which are known to be optimal in dist and location.
+        let mut frontier: Vec<(Node, u64)> = starting_nodes
+            .iter()
+            .cloned()
+            .filter_map(|n| match min_dists.get(&n) {
+                Some(dist) if dist == best_dist => Some((n, *best_dist)),
+                _ => None,
+            })
+            .collect();
+
+        while let Some((node, dist)) = frontier.pop() {
+            visited.insert(node);
+            for (neighbor, cost) in neighbors_reversed(&node, &self.map) {
+                let neighbor_dist = min_dists.get(&neighbor).unwrap();
+
+                if dist.checked_sub(cost) == Some(*neighbor_dist) {
+                    frontier.push((neighbor, *neighbor_dist));
+                }
+            }
+        }
+
+        visited.iter().map(|(p, _)| *p).collect()
+    }
+}
+
+impl FromStr for Maze {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let section = Section::new(s);
+        let grid = section.grid(|cell| match cell {
+            '#' | '.' | 'S' | 'E' => Some(cell),
+            _ => None,
+        })?;
+
+        let mut map = HashMap::new();
+        let mut start = None;
+        let mut exit = None;
+        for (y, row) in grid.into_iter().enumerate() {
+            for (x, cell) in row.into_iter().enumerate() {
+                let pos = Point(x as i64, y as i64);
                 match cell {
+                    'S' => start = Some(pos),
+                    'E' => exit = Some(pos),
+                    _ => {}
+                }
+                let tile = match cell {
                     '#' => MapTile::Wall,
-                    '.' => MapTile::Hall,
-                    'S' => {
-                        start = Some(Point(x as i64, y as i64));
-                        MapTile::Hall
-                    }
-                    'E' => {
-                        exit = Some(Point(x as i64, y as i64));
-                        MapTile::Hall
-                    }
-                    x => panic!("Unknown map element {x}"),
-                },
-            );
+                    _ => MapTile::Hall,
+                };
+                map.insert(pos, tile);
+            }
         }
-    }
 
-    (
-        hm,
-        start.expect("Map should have starting point"),
-        exit.expect("Map should have an exit"),
-    )
+        Ok(Maze {
+            map,
+            start: start.ok_or_else(|| section.error("map should have a starting point"))?,
+            exit: exit.ok_or_else(|| section.error("map should have an exit"))?,
+        })
+    }
 }
 
-fn neighbors(node: &Node, map: &HashMap<Point, MapTile>) -> Vec<(Node, u64)> {
+/// Moves available from `node` and what they cost
+pub fn neighbors(node: &Node, map: &HashMap<Point, MapTile>) -> Vec<(Node, u64)> {
     let (pos, dir) = node;
     let mut neighbors = Vec::new();
 
@@ -124,7 +193,8 @@ fn neighbors_reversed(node: &Node, map: &HashMap<Point, MapTile>) -> Vec<(Node,
     neighbors
 }
 
-type Node = (Point, Direction);
+/// A position and the direction the reindeer is facing
+pub type Node = (Point, Direction);
 
 fn expand(p: Point) -> Vec<Node> {
     vec![
@@ -137,7 +207,11 @@ fn expand(p: Point) -> Vec<Node> {
 
 pub struct ReindeerSolution;
 
-fn find_min_dists(map: &HashMap<Point, MapTile>, start: Point) -> HashMap<(Point, Direction), u64> {
+/// Dijkstra from `start` facing east to every reachable node
+pub fn find_min_dists(
+    map: &HashMap<Point, MapTile>,
+    start: Point,
+) -> HashMap<(Point, Direction), u64> {
     let nodes: Vec<Node> = map
         .iter()
         .filter_map(|(p, t)| (*t != MapTile::Wall).then_some(p))
@@ -164,10 +238,10 @@ fn find_min_dists(map: &HashMap<Point, MapTile>, start: Point) -> HashMap<(Point
     while let Some(Reverse((cost, node))) = queue.pop() {
         //println!("Popping {:?} at cost {:?}", node, cost);
 
-        for (neighbor, transition_cost) in neighbors(&node, &map) {
+        for (neighbor, transition_cost) in neighbors(&node, map) {
             let new_neighbor_cost = cost + transition_cost;
             //println!(" Looking at neighbor {:?} with a cost of {}", neighbor, new_neighbor_cost);
-            let best_neighbor_cost = min_dist.get(&neighbor).unwrap().clone();
+            let best_neighbor_cost = *min_dist.get(&neighbor).unwrap();
 
             if new_neighbor_cost < best_neighbor_cost {
                 //println!("  Neighbor has better cost than previously observed, adding");
@@ -184,56 +258,16 @@ fn find_min_dists(map: &HashMap<Point, MapTile>, start: Point) -> HashMap<(Point
 }
 
 impl Solution for ReindeerSolution {
-    fn part1(puzzle_input: String) -> String {
-        let (map, start, exit) = parse_input(puzzle_input);
-
-        let min_dists = find_min_dists(&map, start);
+    fn part1(puzzle_input: String) -> Answer {
+        let maze: Maze = puzzle_input.parse().unwrap();
 
-        expand(exit)
-            .iter()
-            .map(|n| min_dists.get(n).unwrap())
-            .min()
-            .unwrap()
-            .to_string()
+        maze.best_score().into()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let (map, start, exit) = parse_input(puzzle_input);
-        let min_dists = find_min_dists(&map, start);
-        let starting_nodes = expand(exit);
-
-        let best_dist = expand(exit)
-            .iter()
-            .map(|n| min_dists.get(n).unwrap())
-            .min()
-            .unwrap();
-
-        // Reverse-search through the nodes to find any which could be part of a best path
-        let mut visited = HashSet::new();
-        // Frontier contains nodes, dist which are known to be optimal in dist and location.
-        let mut frontier: Vec<(Node, u64)> = starting_nodes
-            .iter()
-            .cloned()
-            .filter_map(|n| match min_dists.get(&n) {
-                Some(dist) if dist == best_dist => Some((n, *best_dist)),
-                _ => None,
-            })
-            .collect();
-
-        while let Some((node, dist)) = frontier.pop() {
-            visited.insert(node);
-            for (neighbor, cost) in neighbors_reversed(&node, &map) {
-                let neighbor_dist = min_dists.get(&neighbor).unwrap();
+    fn part2(puzzle_input: String) -> Answer {
+        let maze: Maze = puzzle_input.parse().unwrap();
 
-                if dist.checked_sub(cost) == Some(*neighbor_dist) {
-                    frontier.push((neighbor, *neighbor_dist));
-                }
-            }
-        }
-
-        let tiles: HashSet<_> = visited.iter().map(|(p, _)| p).collect();
-
-        tiles.len().to_string()
+        maze.best_path_tiles().len().into()
     }
 }
 
@@ -260,6 +294,17 @@ mod test {
 ###############"
             .to_string();
 
-        assert_eq!(ReindeerSolution::part1(input), "7036".to_string());
+        assert_eq!(ReindeerSolution::part1(input.clone()), "7036".to_string());
+
+        let maze: Maze = input.parse().unwrap();
+        assert_eq!(maze.start, Point(1, 13));
+        assert_eq!(maze.exit, Point(13, 1));
+        assert_eq!(maze.best_path_tiles().len(), 45);
+    }
+
+    #[test]
+    fn maze_needs_start_and_exit() {
+        assert!("#.E\n#..".parse::<Maze>().is_err());
+        assert_eq!("#SE\n#.x".parse::<Maze>().unwrap_err().line, 2);
     }
 }
@@ -1,13 +1,20 @@
+//! Day 8: Resonant Collinearity
+
 use itertools::Itertools;
 use std::{
     collections::{HashMap, HashSet},
     ops::{Add, Sub},
+    str::FromStr,
 };
 
-use crate::solution::Solution;
+use crate::{
+    answer::Answer,
+    parse::{ParseError, Section},
+    solution::Solution,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Point(i32, i32);
+pub struct Point(pub i32, pub i32);
 
 impl Add for Point {
     type Output = Point;
@@ -23,109 +30,136 @@ impl Sub for Point {
     }
 }
 
-#[derive(Debug, Clone)]
-enum MapCell {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapCell {
     Empty,
     Antenna(char),
 }
 
-fn parse_input(puzzle_input: String) -> HashMap<Point, MapCell> {
-    let mut hm = HashMap::new();
-    for (y, row) in puzzle_input.lines().enumerate() {
-        for (x, cell) in row.chars().enumerate() {
-            hm.insert(
-                Point(x as i32, y as i32),
-                match cell {
-                    '.' => MapCell::Empty,
-                    x if x.is_alphanumeric() => MapCell::Antenna(x),
-                    x => panic!("Unknown map element {x}"),
-                },
-            );
-        }
-    }
-
-    hm
+/// The roof map with every antenna and its frequency
+#[derive(Debug, Clone)]
+pub struct AntennaMap {
+    pub cells: HashMap<Point, MapCell>,
 }
 
-fn build_freq_locations(antenna_map: &HashMap<Point, MapCell>) -> HashMap<&char, Vec<Point>>{
-    let mut freq_locations = HashMap::new();
-    for (pos, cell) in antenna_map {
-        if let MapCell::Antenna(a) = cell {
-            freq_locations
-                .entry(a)
-                .or_insert(Vec::new())
-                .push(pos.clone());
+impl AntennaMap {
+    /// Positions of the antennas, grouped by frequency
+    pub fn frequency_locations(&self) -> HashMap<&char, Vec<Point>> {
+        let mut freq_locations = HashMap::new();
+        for (pos, cell) in &self.cells {
+            if let MapCell::Antenna(a) = cell {
+                freq_locations.entry(a).or_insert(Vec::new()).push(*pos);
+            }
         }
+        freq_locations
     }
-    freq_locations
-}
-
-pub struct Resonant;
-
-impl Solution for Resonant {
-    fn part1(puzzle_input: String) -> String {
-        let antenna_map = parse_input(puzzle_input);
-        let freq_locations = build_freq_locations(&antenna_map);
 
+    /// Antinodes on the map, either only the pair at twice the distance or every point in line
+    /// with two antennas when `harmonics` is set
+    pub fn antinodes(&self, harmonics: bool) -> HashSet<Point> {
         // find each pair of same-freq antenna
         let mut antinodes = HashSet::new();
-        for (_freq, locations) in freq_locations {
+        for (_freq, locations) in self.frequency_locations() {
             // For each pair, calculate the two points
             for pair in locations.iter().combinations(2) {
                 let a = pair[0];
                 let b = pair[1];
                 let diff = *a - *b;
-                antinodes.insert(*a + diff);
-                antinodes.insert(*a - diff - diff);
-            }
-        }
-
-        antinodes
-            .iter()
-            .filter(|a| match &antenna_map.get(a) {
-                Some(MapCell::Antenna(_)) => true,
-                Some(_) => true,
-                None => false,
-            })
-            .count()
-            .to_string()
-    }
 
-    fn part2(puzzle_input: String) -> String {
-        let antenna_map = parse_input(puzzle_input);
-        let freq_locations = build_freq_locations(&antenna_map);
+                if !harmonics {
+                    antinodes.insert(*a + diff);
+                    antinodes.insert(*a - diff - diff);
+                    continue;
+                }
 
-        // find each pair of same-freq antenna
-        let mut antinodes = HashSet::new();
-        for (_freq, locations) in freq_locations {
-            // For each pair, calculate the two points
-            for pair in locations.iter().combinations(2) {
-                let a = pair[0];
-                let b = pair[1];
-                let diff = *a - *b;
                 // Go until we leave the map in either direction
                 let mut current = *a;
-                while let Some(_) = &antenna_map.get(&current) {
+                while self.cells.contains_key(&current) {
                     antinodes.insert(current);
                     current = current + diff;
                 }
 
                 current = *a;
-                while let Some(_) = &antenna_map.get(&current) {
+                while self.cells.contains_key(&current) {
                     antinodes.insert(current);
                     current = current - diff;
                 }
             }
         }
 
+        antinodes.retain(|a| self.cells.contains_key(a));
         antinodes
-            .iter()
-            .filter(|a| match &antenna_map.get(a) {
-                Some(MapCell::Antenna(_)) => true,
-                Some(_) => true,
-                None => false,
-            })
-            .count()
-            .to_string()
+    }
+}
+
+impl FromStr for AntennaMap {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let grid = Section::new(s).grid(|cell| match cell {
+            '.' => Some(MapCell::Empty),
+            x if x.is_alphanumeric() => Some(MapCell::Antenna(x)),
+            _ => None,
+        })?;
+
+        let mut cells = HashMap::new();
+        for (y, row) in grid.into_iter().enumerate() {
+            for (x, cell) in row.into_iter().enumerate() {
+                cells.insert(Point(x as i32, y as i32), cell);
+            }
+        }
+
+        Ok(AntennaMap { cells })
+    }
+}
+
+pub struct Resonant;
+
+impl Solution for Resonant {
+    fn part1(puzzle_input: String) -> Answer {
+        let antenna_map: AntennaMap = puzzle_input.parse().unwrap();
+
+        antenna_map.antinodes(false).len().into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let antenna_map: AntennaMap = puzzle_input.parse().unwrap();
+
+        antenna_map.antinodes(true).len().into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+    #[test]
+    fn parses_map() {
+        let map: AntennaMap = EXAMPLE.parse().unwrap();
+        assert_eq!(map.cells.len(), 144);
+        assert_eq!(map.cells.get(&Point(8, 1)), Some(&MapCell::Antenna('0')));
+        assert_eq!(map.frequency_locations().get(&'A').map(Vec::len), Some(3));
+
+        assert_eq!("..\n.#".parse::<AntennaMap>().unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn example() {
+        let map: AntennaMap = EXAMPLE.parse().unwrap();
+        assert_eq!(map.antinodes(false).len(), 14);
+        assert_eq!(map.antinodes(true).len(), 34);
     }
 }
@@ -1,20 +1,28 @@
+//! Day 14: Restroom Redoubt
+
 use std::{
     collections::HashSet,
     ops::{Add, Mul},
+    str::FromStr,
 };
 
 use itertools::Itertools;
 
-use crate::solution::Solution;
+use crate::{
+    answer::Answer,
+    parse::{self, Line, ParseError, ParseResult},
+    solution::Solution,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Robot {
-    pos: Point,
-    vel: Point,
+pub struct Robot {
+    pub pos: Point,
+    pub vel: Point,
 }
 
 impl Robot {
-    fn simulate(&self, steps: i64, x_max: i64, y_max: i64) -> Robot {
+    /// Where the robot is after `steps` seconds, wrapping around the `x_max` by `y_max` room
+    pub fn simulate(&self, steps: i64, x_max: i64, y_max: i64) -> Robot {
         let new_pos = self.pos + (self.vel * steps);
         let scaled = Point(new_pos.0.rem_euclid(x_max), new_pos.1.rem_euclid(y_max));
 
@@ -25,8 +33,21 @@ impl Robot {
     }
 }
 
+impl FromStr for Robot {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [px, py, vx, vy] = Line::new(s).ints_n()?;
+
+        Ok(Robot {
+            pos: Point(px, py),
+            vel: Point(vx, vy),
+        })
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Point(i64, i64);
+pub struct Point(pub i64, pub i64);
 
 impl Mul<i64> for Point {
     type Output = Point;
@@ -44,37 +65,30 @@ impl Add for Point {
 
 pub struct RestroomSolution;
 
-fn parse_input(puzzle_input: String) -> Vec<Robot> {
-    puzzle_input
-        .lines()
-        .map(|l| {
-            let (pstring, vstring) = l.split_once(' ').unwrap();
-            let (px, py) = pstring[2..].split_once(',').unwrap();
-            let (vx, vy) = vstring[2..].split_once(',').unwrap();
-
-            Robot {
-                pos: Point(px.parse().unwrap(), py.parse().unwrap()),
-                vel: Point(vx.parse().unwrap(), vy.parse().unwrap()),
-            }
-        })
-        .collect()
+pub fn parse_input(puzzle_input: &str) -> ParseResult<Vec<Robot>> {
+    parse::parse_lines(puzzle_input)
 }
 
-fn print_robots(robots: &[Robot], x_max: i64, y_max: i64) {
+/// Draws the room with a `#` wherever there's at least one robot
+pub fn render_robots(robots: &[Robot], x_max: i64, y_max: i64) -> String {
     let positions: HashSet<_> = robots.iter().map(|r| r.pos).collect();
+    let mut rendering = String::new();
     for y in 0..y_max {
         for x in 0..x_max {
-            if positions.contains(&Point(x as i64, y as i64)) {
-                print!("#")
+            if positions.contains(&Point(x, y)) {
+                rendering.push('#')
             } else {
-                print!(".")
+                rendering.push('.')
             }
         }
-        print!("\n");
+        rendering.push('\n');
     }
+
+    rendering
 }
 
-fn quadrant(pos: Point, x_max: i64, y_max: i64) -> Option<i64> {
+/// Which quadrant of the room `pos` is in, robots on the middle lines are in none
+pub fn quadrant(pos: Point, x_max: i64, y_max: i64) -> Option<i64> {
     match (pos.0, pos.1) {
         (x, y) if x >= 0 && x < x_max / 2 && y >= 0 && y < y_max / 2 => Some(1),
         (x, y) if x > x_max / 2 && x < x_max && y >= 0 && y < y_max / 2 => Some(2),
@@ -85,8 +99,8 @@ fn quadrant(pos: Point, x_max: i64, y_max: i64) -> Option<i64> {
 }
 
 impl Solution for RestroomSolution {
-    fn part1(puzzle_input: String) -> String {
-        let robots = parse_input(puzzle_input);
+    fn part1(puzzle_input: String) -> Answer {
+        let robots = parse_input(&puzzle_input).unwrap();
         let (x_max, y_max) = if robots.len() > 12 {
             (101, 103)
         } else {
@@ -100,15 +114,15 @@ impl Solution for RestroomSolution {
             .counts_by(|n| n)
             .values()
             .product::<usize>()
-            .to_string()
+            .into()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let robots = parse_input(puzzle_input);
+    fn part2(puzzle_input: String) -> Answer {
+        let robots = parse_input(&puzzle_input).unwrap();
         let (x_max, y_max) = if robots.len() > 12 {
             (101, 103)
         } else {
-            return "".to_string();
+            return Answer::Text(String::new());
         };
         let max_steps = x_max * y_max;
 
@@ -132,9 +146,7 @@ impl Solution for RestroomSolution {
             .map(|r| r.simulate(step, x_max, y_max))
             .collect();
 
-        print_robots(&robs, x_max, y_max);
-
-        step.to_string()
+        Answer::from(step).with_rendering(render_robots(&robs, x_max, y_max))
     }
 }
 
@@ -145,14 +157,20 @@ mod test {
     #[test]
     fn input_parsing() {
         assert_eq!(
-            parse_input("p=4,72 v=24,-91".to_string()),
-            vec![Robot {
+            parse_input("p=4,72 v=24,-91"),
+            Ok(vec![Robot {
                 pos: Point(4, 72),
                 vel: Point(24, -91),
-            }]
+            }])
         );
     }
 
+    #[test]
+    fn parse_errors_have_lines() {
+        let err = parse_input("p=0,4 v=3,-3\np=6,3 v=-1").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
     #[test]
     fn robots_move_correctly() {
         let r = Robot {
@@ -1,69 +1,143 @@
-use std::collections::{HashMap, HashSet};
+//! Day 19: Linen Layout
 
-use crate::solution::Solution;
+use std::{collections::HashSet, str::FromStr};
 
-fn parse_input(puzzle_input: &str) -> (Vec<&str>, Vec<&str>) {
-    let (pieces, targets) = puzzle_input.split_once("\n\n").unwrap();
-    (pieces.split(", ").collect(), targets.lines().collect())
+use crate::{
+    answer::Answer,
+    memo::{Memo, MemoStats},
+    parse::{self, ParseError},
+    solution::Solution,
+};
+
+/// The available towel patterns and the designs we're asked to make from them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Onsen {
+    pub patterns: Vec<String>,
+    pub designs: Vec<String>,
 }
 
-fn count_builds(
+impl Onsen {
+    /// Number of ways to lay out each design, in input order
+    pub fn arrangements(&self) -> Vec<u64> {
+        self.arrangements_with_stats().0
+    }
+
+    /// [`Onsen::arrangements`] along with how well the memos did, summed over the designs
+    pub fn arrangements_with_stats(&self) -> (Vec<u64>, MemoStats) {
+        let piece_set: HashSet<&str> = self.patterns.iter().map(String::as_str).collect();
+        let max_piece_size = piece_set.iter().cloned().map(str::len).max().unwrap_or(0);
+
+        let mut stats = MemoStats::default();
+        let arrangements = self
+            .designs
+            .iter()
+            .map(|design| {
+                let mut memo = Memo::new();
+                let builds = count_builds(&piece_set, design, max_piece_size, &mut memo);
+                stats = stats.merge(memo.stats());
+                builds
+            })
+            .collect();
+
+        (arrangements, stats)
+    }
+}
+
+impl FromStr for Onsen {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [pieces, targets] = parse::sections_n(s)?;
+
+        let patterns: Vec<String> = pieces.text.split(", ").map(str::to_string).collect();
+        if let Some(bad) = patterns
+            .iter()
+            .find(|p| p.is_empty() || p.contains(char::is_whitespace))
+        {
+            return Err(pieces.error(format!("invalid towel pattern {bad:?}")));
+        }
+
+        Ok(Onsen {
+            patterns,
+            designs: targets.lines().map(|line| line.text.to_string()).collect(),
+        })
+    }
+}
+
+/// Number of ways `target` can be built by concatenating `pieces`
+pub fn count_builds<'a>(
     pieces: &HashSet<&str>,
-    target: &str,
+    target: &'a str,
     max_piece_size: usize,
-    mem: &mut HashMap<String, u64>,
+    mem: &mut Memo<&'a str, u64>,
 ) -> u64 {
     if target.is_empty() {
         return 1;
     }
 
-    if let Some(count) = mem.get(target) {
-        return *count;
-    }
-
-    let mut target_builds = 0;
-    for i in 1..(max_piece_size + 1).min(target.len() + 1) {
-        if pieces.contains(&target[..i]) {
-            let num_suffix_builds = count_builds(pieces, &target[i..], max_piece_size, mem);
+    mem.get_or_compute(target, |mem| {
+        let mut target_builds = 0;
+        for i in 1..(max_piece_size + 1).min(target.len() + 1) {
+            if pieces.contains(&target[..i]) {
+                let num_suffix_builds = count_builds(pieces, &target[i..], max_piece_size, mem);
 
-            target_builds += num_suffix_builds
+                target_builds += num_suffix_builds
+            }
         }
-    }
 
-    mem.insert(target.to_string(), target_builds);
-    target_builds
+        target_builds
+    })
 }
 
 pub struct TowelSolution;
 
 impl Solution for TowelSolution {
-    fn part1(puzzle_input: String) -> String {
-        let (pieces, targets) = parse_input(&puzzle_input);
-        let piece_set: HashSet<_> = pieces.iter().cloned().collect();
+    fn part1(puzzle_input: String) -> Answer {
+        let onsen: Onsen = puzzle_input.parse().unwrap();
 
-        let max_piece_size = pieces.iter().cloned().map(str::len).max().unwrap();
+        let (arrangements, stats) = onsen.arrangements_with_stats();
+        let possible = arrangements.iter().filter(|&&builds| builds > 0).count();
 
-        targets
-            .iter()
-            .cloned()
-            .filter(|target| {
-                count_builds(&piece_set, *target, max_piece_size, &mut HashMap::new()) > 0
-            })
-            .count()
-            .to_string()
+        Answer::from(possible).with_explanation(format!("memo: {stats}"))
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let (pieces, targets) = parse_input(&puzzle_input);
-        let piece_set: HashSet<_> = pieces.iter().cloned().collect();
+    fn part2(puzzle_input: String) -> Answer {
+        let onsen: Onsen = puzzle_input.parse().unwrap();
 
-        let max_piece_size = pieces.iter().cloned().map(str::len).max().unwrap();
+        let (arrangements, stats) = onsen.arrangements_with_stats();
 
-        targets
-            .iter()
-            .cloned()
-            .map(|target| count_builds(&piece_set, target, max_piece_size, &mut HashMap::new()))
-            .sum::<u64>()
-            .to_string()
+        Answer::from(arrangements.iter().sum::<u64>()).with_explanation(format!("memo: {stats}"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb";
+
+    #[test]
+    fn parses_onsen() {
+        let onsen: Onsen = EXAMPLE.parse().unwrap();
+        assert_eq!(onsen.patterns.len(), 8);
+        assert_eq!(onsen.designs[0], "brwrr");
+
+        assert!("r, wr".parse::<Onsen>().is_err());
+        assert!("r, , b\n\nbr".parse::<Onsen>().is_err());
+    }
+
+    #[test]
+    fn example() {
+        let onsen: Onsen = EXAMPLE.parse().unwrap();
+        assert_eq!(onsen.arrangements(), vec![2, 1, 4, 6, 0, 1, 2, 0]);
     }
 }
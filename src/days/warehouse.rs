@@ -1,12 +1,19 @@
+//! Day 15: Warehouse Woes
+
 use core::panic;
-use std::{collections::HashMap, ops::Add};
+use std::{collections::HashMap, ops::Add, str::FromStr};
 
-use crate::{solution::Solution, utils::wait_for_input};
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError},
+    solution::Solution,
+};
 
+#[allow(dead_code)]
 const DIRS: [Point; 4] = [Point(0, 1), Point(1, 0), Point(0, -1), Point(-1, 0)];
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
-struct Point(i64, i64);
+pub struct Point(pub i64, pub i64);
 
 impl Add for Point {
     type Output = Point;
@@ -16,7 +23,7 @@ impl Add for Point {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
-enum Direction {
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -24,7 +31,7 @@ enum Direction {
 }
 
 impl Direction {
-    fn as_point(&self) -> Point {
+    pub fn as_point(&self) -> Point {
         match self {
             Direction::Up => Point(0, -1),
             Direction::Down => Point(0, 1),
@@ -35,7 +42,7 @@ impl Direction {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum MapTile {
+pub enum MapTile {
     Wall,
     Box,
     Space,
@@ -44,7 +51,7 @@ enum MapTile {
 }
 
 /// Find the 'root' coord for a 2-wide box
-fn root(map: &HashMap<Point, MapTile>, p: Point) -> Point {
+pub fn root(map: &HashMap<Point, MapTile>, p: Point) -> Point {
     match map.get(&p).unwrap() {
         MapTile::LBox => p,
         MapTile::RBox => p + Direction::Left.as_point(),
@@ -52,48 +59,101 @@ fn root(map: &HashMap<Point, MapTile>, p: Point) -> Point {
     }
 }
 
-fn parse_input(puzzle_input: String) -> (HashMap<Point, MapTile>, Vec<Direction>, Point) {
-    let (map_string, move_string) = puzzle_input.split_once("\n\n").unwrap();
-    let mut robot = None;
-
-    let mut map = HashMap::new();
-    for (y, row) in map_string.lines().enumerate() {
-        for (x, c) in row.chars().enumerate() {
-            let pos = Point(x as i64, y as i64);
-            let tile = match c {
-                '#' => MapTile::Wall,
-                'O' => MapTile::Box,
-                '.' => MapTile::Space,
-                '@' => {
+/// The warehouse map, the robot's planned moves and where it starts
+#[derive(Debug, Clone)]
+pub struct Warehouse {
+    pub map: HashMap<Point, MapTile>,
+    pub moves: Vec<Direction>,
+    pub robot: Point,
+}
+
+impl Warehouse {
+    /// The same warehouse with everything except the robot twice as wide
+    pub fn expanded(&self) -> Warehouse {
+        Warehouse {
+            map: expand_map(self.map.clone()),
+            moves: self.moves.clone(),
+            robot: Point(self.robot.0 * 2, self.robot.1),
+        }
+    }
+
+    /// Performs every move, pushing boxes out of the way when they can move
+    pub fn run(&mut self) {
+        for move_ in self.moves.iter().copied() {
+            //print_map(&self.map, &self.robot);
+            //wait_for_input();
+
+            let target_position = self.robot + move_.as_point();
+            if can_move_box(target_position, move_, &self.map) {
+                move_box(target_position, move_, &mut self.map);
+                self.robot = target_position;
+            }
+        }
+    }
+
+    /// Sum of the GPS coordinates of every box, measured from the left edge of wide boxes
+    pub fn gps_sum(&self) -> i64 {
+        self.map
+            .iter()
+            .filter_map(|(point, tile)| match tile {
+                MapTile::Box | MapTile::LBox => Some(point.1 * 100 + point.0),
+                _ => None,
+            })
+            .sum::<i64>()
+    }
+}
+
+impl FromStr for Warehouse {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [map_section, move_section] = parse::sections_n(s)?;
+
+        let grid = map_section.grid(|c| match c {
+            '#' => Some((MapTile::Wall, false)),
+            'O' => Some((MapTile::Box, false)),
+            '.' => Some((MapTile::Space, false)),
+            '@' => Some((MapTile::Space, true)),
+            _ => None,
+        })?;
+
+        let mut robot = None;
+        let mut map = HashMap::new();
+        for (y, row) in grid.into_iter().enumerate() {
+            for (x, (tile, is_robot)) in row.into_iter().enumerate() {
+                let pos = Point(x as i64, y as i64);
+                if is_robot {
                     robot = Some(pos);
-                    MapTile::Space
                 }
-                _ => panic!("Unexpected map character {c}"),
-            };
-            map.insert(pos, tile);
+                map.insert(pos, tile);
+            }
         }
-    }
 
-    let moves: Vec<_> = move_string
-        .trim()
-        .chars()
-        .filter_map(|move_| match move_ {
-            '^' => Some(Direction::Up),
-            '>' => Some(Direction::Right),
-            'v' => Some(Direction::Down),
-            '<' => Some(Direction::Left),
-            '\n' => None,
-            _ => panic!("bad direction '{move_}'"),
-        })
-        .collect();
+        let mut moves = Vec::new();
+        for line in move_section.lines() {
+            for move_ in line.text.trim().chars() {
+                moves.push(match move_ {
+                    '^' => Direction::Up,
+                    '>' => Direction::Right,
+                    'v' => Direction::Down,
+                    '<' => Direction::Left,
+                    _ => return Err(line.error(format!("bad direction '{move_}'"))),
+                });
+            }
+        }
 
-    (map, moves, robot.unwrap())
+        Ok(Warehouse {
+            map,
+            moves,
+            robot: robot.ok_or_else(|| map_section.error("map should contain the robot"))?,
+        })
+    }
 }
 
-fn expand_map(map: HashMap<Point, MapTile>) -> HashMap<Point, MapTile> {
+pub fn expand_map(map: HashMap<Point, MapTile>) -> HashMap<Point, MapTile> {
     map.iter()
         .flat_map(|(p, tile)| {
-            let (ltile, rtile) = match tile.clone() {
+            let (ltile, rtile) = match *tile {
                 MapTile::Box => (MapTile::LBox, MapTile::RBox),
                 t => (t, t),
             };
@@ -106,7 +166,9 @@ fn expand_map(map: HashMap<Point, MapTile>) -> HashMap<Point, MapTile> {
         .collect()
 }
 
-fn print_map(map: &HashMap<Point, MapTile>, robot: &Point) {
+/// Draws the warehouse the way the puzzle does
+pub fn render_map(map: &HashMap<Point, MapTile>, robot: &Point) -> String {
+    let mut rendering = String::new();
     let x_min = map.keys().map(|p| p.0).max().unwrap();
     let y_min = map.keys().map(|p| p.1).max().unwrap();
 
@@ -114,31 +176,34 @@ fn print_map(map: &HashMap<Point, MapTile>, robot: &Point) {
         for x in 0..x_min + 1 {
             let p = Point(x, y);
             if p == *robot {
-                print!("@");
+                rendering.push('@');
                 continue;
             }
 
             let c = match map.get(&p).unwrap() {
-                MapTile::Wall => "#",
-                MapTile::Space => ".",
-                MapTile::Box => "O",
-                MapTile::LBox => "[",
-                MapTile::RBox => "]",
+                MapTile::Wall => '#',
+                MapTile::Space => '.',
+                MapTile::Box => 'O',
+                MapTile::LBox => '[',
+                MapTile::RBox => ']',
             };
-            print!("{c}");
+            rendering.push(c);
         }
-        print!("\n");
+        rendering.push('\n');
     }
+
+    rendering
 }
 
-fn can_move_box(candidate: Point, dir: Direction, map: &HashMap<Point, MapTile>) -> bool {
+/// Whether whatever is at `candidate` can be pushed one step in `dir`
+pub fn can_move_box(candidate: Point, dir: Direction, map: &HashMap<Point, MapTile>) -> bool {
     match map.get(&candidate) {
-        Some(MapTile::Space) => return true,
-        Some(MapTile::Box) => return can_move_box(candidate + dir.as_point(), dir, map),
+        Some(MapTile::Space) => true,
+        Some(MapTile::Box) => can_move_box(candidate + dir.as_point(), dir, map),
         Some(MapTile::LBox) | Some(MapTile::RBox) => match dir {
             // L/R behaves normally
             Direction::Left | Direction::Right => {
-                return can_move_box(candidate + dir.as_point(), dir, map)
+                can_move_box(candidate + dir.as_point(), dir, map)
             }
             // Also check the siblings movability
             _ => {
@@ -149,12 +214,12 @@ fn can_move_box(candidate: Point, dir: Direction, map: &HashMap<Point, MapTile>)
                     && can_move_box(sibling_pos + dir.as_point(), dir, map)
             }
         },
-        _ => return false,
+        _ => false,
     }
 }
 
 /// Called on a root movable map tile which recurses to move all the necessary knock-on tiles
-fn move_box(candidate: Point, dir: Direction, map: &mut HashMap<Point, MapTile>) {
+pub fn move_box(candidate: Point, dir: Direction, map: &mut HashMap<Point, MapTile>) {
     let tile = map.get(&candidate).unwrap();
     match tile {
         MapTile::Wall => panic!("Tried to move into a wall"),
@@ -163,7 +228,7 @@ fn move_box(candidate: Point, dir: Direction, map: &mut HashMap<Point, MapTile>)
             move_box(candidate + dir.as_point(), dir, map);
 
             // Then move this thing
-            let tile = map.get(&candidate).unwrap().clone();
+            let tile = *map.get(&candidate).unwrap();
             map.insert(candidate, MapTile::Space);
             map.insert(candidate + dir.as_point(), tile);
         }
@@ -171,7 +236,7 @@ fn move_box(candidate: Point, dir: Direction, map: &mut HashMap<Point, MapTile>)
             // Move dependencies
             Direction::Left | Direction::Right => {
                 move_box(candidate + dir.as_point(), dir, map);
-                let tile = map.get(&candidate).unwrap().clone();
+                let tile = *map.get(&candidate).unwrap();
                 map.insert(candidate, MapTile::Space);
                 map.insert(candidate + dir.as_point(), tile);
             }
@@ -189,10 +254,10 @@ fn move_box(candidate: Point, dir: Direction, map: &mut HashMap<Point, MapTile>)
                 }
 
                 // Then move myself and my sibling thing
-                let tile = map.get(&root_pos).unwrap().clone();
+                let tile = *map.get(&root_pos).unwrap();
                 map.insert(root_pos, MapTile::Space);
                 map.insert(root_pos + dir.as_point(), tile);
-                let tile = map.get(&sibling_pos).unwrap().clone();
+                let tile = *map.get(&sibling_pos).unwrap();
                 map.insert(sibling_pos, MapTile::Space);
                 map.insert(sibling_pos + dir.as_point(), tile);
             }
@@ -204,52 +269,69 @@ fn move_box(candidate: Point, dir: Direction, map: &mut HashMap<Point, MapTile>)
 pub struct WarehouseSolution;
 
 impl Solution for WarehouseSolution {
-    fn part1(puzzle_input: String) -> String {
-        let (mut map, moves, robot) = parse_input(puzzle_input);
-
-        let mut current_position = robot.clone();
-        for move_ in moves {
-            let target_position = current_position + move_.as_point();
-            if can_move_box(target_position, move_, &map) {
-                move_box(target_position, move_, &mut map);
-                current_position = target_position;
-            }
-        }
+    fn part1(puzzle_input: String) -> Answer {
+        let mut warehouse: Warehouse = puzzle_input.parse().unwrap();
 
-        map.iter()
-            .filter_map(|(point, tile)| match tile {
-                MapTile::Box => Some(point.1 * 100 + point.0),
-                _ => None,
-            })
-            .sum::<i64>()
-            .to_string()
+        warehouse.run();
+
+        warehouse.gps_sum().into()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let (mut map, moves, mut robot) = parse_input(puzzle_input);
-        robot.0 = robot.0 * 2;
-        map = expand_map(map);
+    fn part2(puzzle_input: String) -> Answer {
+        let mut warehouse = puzzle_input.parse::<Warehouse>().unwrap().expanded();
 
-        let mut current_position = robot.clone();
-        for move_ in moves {
-            //print_map(&map, &current_position);
-            //wait_for_input();
+        warehouse.run();
 
-            let target_position = current_position + move_.as_point();
-            if can_move_box(target_position, move_, &map) {
-                move_box(target_position, move_, &mut map);
-                current_position = target_position;
-            }
-        }
+        Answer::from(warehouse.gps_sum())
+            .with_rendering(render_map(&warehouse.map, &warehouse.robot))
+    }
+}
 
-        print_map(&map, &current_position);
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<";
+
+    #[test]
+    fn parses_warehouse() {
+        let warehouse: Warehouse = EXAMPLE.parse().unwrap();
+        assert_eq!(warehouse.robot, Point(2, 2));
+        assert_eq!(warehouse.moves.len(), 15);
+        assert_eq!(warehouse.map.get(&Point(3, 1)), Some(&MapTile::Box));
+
+        let err = "#@.#\n\n<>\n^x".parse::<Warehouse>().unwrap_err();
+        assert_eq!(err.line, 4);
+    }
 
-        map.iter()
-            .filter_map(|(point, tile)| match tile {
-                MapTile::LBox => Some(point.1 * 100 + point.0),
-                _ => None,
-            })
-            .sum::<i64>()
-            .to_string()
+    #[test]
+    fn pushes_boxes() {
+        let mut warehouse: Warehouse = EXAMPLE.parse().unwrap();
+        warehouse.run();
+        assert_eq!(warehouse.gps_sum(), 2028);
+
+        let mut wide = "#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vv<<^^<<^^"
+            .parse::<Warehouse>()
+            .unwrap()
+            .expanded();
+        wide.run();
+        assert_eq!(wide.gps_sum(), 105 + 207 + 306);
     }
 }
@@ -0,0 +1,263 @@
+//! Checking a circuit is a ripple-carry adder, and repairing it when it isn't.
+//!
+//! Bit `i` of a ripple-carry adder is a full adder:
+//!
+//! ```text
+//! x_i XOR y_i      -> sum_i
+//! x_i AND y_i      -> and_i
+//! sum_i XOR carry  -> z_i
+//! sum_i AND carry  -> pass_i
+//! and_i OR pass_i  -> carry_i (the last carry is the top z bit)
+//! ```
+//!
+//! Bit 0 has no carry in, so it's just `x00 XOR y00 -> z00` and `x00 AND y00 -> carry_0`. Gates
+//! whose output doesn't play the role its operation and inputs say it should are miswired, and the
+//! swaps which fix the circuit are searched for among them.
+
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+
+use super::{compiled::CompiledCircuit, Gate, GateOp};
+
+/// A gate output which doesn't fit into the adder where it should
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Miswired {
+    pub wire: String,
+    pub reason: &'static str,
+}
+
+fn is_input(wire: &str) -> bool {
+    wire.starts_with('x') || wire.starts_with('y')
+}
+
+/// Number of bits in each input
+pub fn input_bits(gates: &[Gate]) -> usize {
+    gates
+        .iter()
+        .flat_map(|gate| &gate.inputs)
+        .filter(|wire| wire.starts_with('x'))
+        .unique()
+        .count()
+}
+
+/// Every gate output which breaks the full adder pattern, in gate order
+pub fn check_structure(gates: &[Gate]) -> Vec<Miswired> {
+    let last_z = format!("z{:02}", input_bits(gates));
+
+    // Which operations read each wire
+    let mut readers: HashMap<&str, HashSet<GateOp>> = HashMap::new();
+    for gate in gates {
+        for input in &gate.inputs {
+            readers.entry(input).or_default().insert(gate.op);
+        }
+    }
+    let read_by = |wire: &str, op| readers.get(wire).is_some_and(|ops| ops.contains(&op));
+
+    let mut miswired = Vec::new();
+    for gate in gates {
+        let output = gate.output.as_str();
+        let from_inputs = gate.inputs.iter().all(|wire| is_input(wire));
+        let first_bit = gate.inputs.iter().all(|wire| wire.ends_with("00"));
+
+        let reason = match gate.op {
+            _ if gate.inputs.len() != 2 => Some("adder gates have two inputs"),
+            _ if output == last_z && gate.op != GateOp::Or => {
+                Some("the final carry should come from an OR")
+            }
+            _ if output.starts_with('z') && output != last_z && gate.op != GateOp::Xor => {
+                Some("z wires should come from an XOR")
+            }
+            GateOp::Xor if from_inputs && first_bit => {
+                (output != "z00").then_some("x00 XOR y00 should be z00")
+            }
+            GateOp::Xor if from_inputs => (!read_by(output, GateOp::Xor))
+                .then_some("half sum isn't added to the carry by an XOR"),
+            GateOp::Xor => (!output.starts_with('z')).then_some("full sum doesn't go to a z wire"),
+            GateOp::And if from_inputs && first_bit => {
+                (!read_by(output, GateOp::Xor)).then_some("first carry isn't used by bit 1")
+            }
+            GateOp::And => (!read_by(output, GateOp::Or)).then_some("AND doesn't feed a carry OR"),
+            GateOp::Or => (output != last_z && !read_by(output, GateOp::Xor))
+                .then_some("carry isn't used by the next bit"),
+            GateOp::Not | GateOp::Nand | GateOp::Nor | GateOp::Xnor | GateOp::Const(_) => {
+                Some("adders are only made of AND, OR and XOR")
+            }
+        };
+
+        if let Some(reason) = reason {
+            miswired.push(Miswired {
+                wire: gate.output.clone(),
+                reason,
+            });
+        }
+    }
+
+    miswired
+}
+
+/// Whether the circuit adds its inputs correctly. Every bit is checked on its own, with a carry
+/// in, and with a carry running the whole width.
+pub fn is_adder(gates: &[Gate]) -> bool {
+    let Ok(circuit) = CompiledCircuit::new(gates) else {
+        return false;
+    };
+    let bits = circuit.bus('x').len();
+    if bits == 0 || bits >= 64 || circuit.bus('z').len() != bits + 1 {
+        return false;
+    }
+
+    let mask = (1 << bits) - 1;
+    let mut cases = vec![(mask, 0), (mask, 1), (1, mask), (mask, mask)];
+    for i in 0..bits {
+        let bit = 1 << i;
+        cases.extend([(bit, 0), (0, bit), (bit, bit), (bit | bit >> 1, bit >> 1)]);
+    }
+
+    cases.into_iter().all(|(x, y)| circuit.add(x, y) == x + y)
+}
+
+/// The gates with each pair of outputs exchanged
+pub fn swap_outputs(gates: &[Gate], swaps: &[(String, String)]) -> Vec<Gate> {
+    let mut renames = HashMap::new();
+    for (a, b) in swaps {
+        renames.insert(a, b);
+        renames.insert(b, a);
+    }
+
+    gates
+        .iter()
+        .map(|gate| Gate {
+            output: renames
+                .get(&gate.output)
+                .map_or_else(|| gate.output.clone(), |other| other.to_string()),
+            ..gate.clone()
+        })
+        .collect()
+}
+
+/// Every way of splitting `wires` into pairs
+fn pairings(wires: &[String]) -> Vec<Vec<(String, String)>> {
+    let Some((first, rest)) = wires.split_first() else {
+        return vec![Vec::new()];
+    };
+
+    let mut pairings = Vec::new();
+    for (i, partner) in rest.iter().enumerate() {
+        let mut remaining = rest.to_vec();
+        remaining.remove(i);
+        for mut pairing in self::pairings(&remaining) {
+            pairing.push((first.clone(), partner.clone()));
+            pairings.push(pairing);
+        }
+    }
+
+    pairings
+}
+
+/// The fewest output swaps (at most `max_swaps`) which turn the circuit into a working adder. Only
+/// wires [`check_structure`] flags are considered.
+pub fn find_swaps(gates: &[Gate], max_swaps: usize) -> Option<Vec<(String, String)>> {
+    let suspects: Vec<String> = check_structure(gates)
+        .into_iter()
+        .map(|miswired| miswired.wire)
+        .unique()
+        .collect();
+
+    (0..=max_swaps.min(suspects.len() / 2)).find_map(|swaps| {
+        suspects
+            .iter()
+            .cloned()
+            .combinations(swaps * 2)
+            .flat_map(|wires| pairings(&wires))
+            .find(|pairing| is_adder(&swap_outputs(gates, pairing)))
+    })
+}
+
+/// The wires in `swaps`, sorted, which is how the puzzle wants them
+pub fn swapped_wires(swaps: &[(String, String)]) -> Vec<String> {
+    swaps
+        .iter()
+        .flat_map(|(a, b)| [a.clone(), b.clone()])
+        .sorted()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::fixtures::ripple_adder;
+    use super::*;
+
+    fn swaps(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_working_adders() {
+        let gates = ripple_adder(8);
+        assert_eq!(input_bits(&gates), 8);
+        assert_eq!(check_structure(&gates), vec![]);
+        assert!(is_adder(&gates));
+        assert_eq!(find_swaps(&gates, 4), Some(vec![]));
+    }
+
+    #[test]
+    fn flags_miswired_outputs() {
+        let gates = swap_outputs(&ripple_adder(8), &swaps(&[("z05", "p05"), ("s02", "a02")]));
+        assert!(!is_adder(&gates));
+
+        let flagged: Vec<_> = check_structure(&gates)
+            .into_iter()
+            .map(|miswired| (miswired.wire, miswired.reason))
+            .sorted()
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (
+                    "a02".to_string(),
+                    "half sum isn't added to the carry by an XOR"
+                ),
+                ("p05".to_string(), "full sum doesn't go to a z wire"),
+                ("s02".to_string(), "AND doesn't feed a carry OR"),
+                ("z05".to_string(), "z wires should come from an XOR"),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_swaps() {
+        let broken = swaps(&[("z05", "p05"), ("s02", "a02"), ("c06", "z07")]);
+        let gates = swap_outputs(&ripple_adder(12), &broken);
+
+        let found = find_swaps(&gates, 4).unwrap();
+        assert!(is_adder(&swap_outputs(&gates, &found)));
+        assert_eq!(
+            swapped_wires(&found),
+            vec!["a02", "c06", "p05", "s02", "z05", "z07"]
+        );
+        assert_eq!(find_swaps(&gates, 2), None);
+    }
+
+    #[test]
+    fn flags_other_gates() {
+        let mut gates = ripple_adder(4);
+        gates[1].op = GateOp::Nand;
+        gates[3].inputs.push("x03".to_string());
+
+        let flagged: Vec<_> = check_structure(&gates)
+            .into_iter()
+            .map(|miswired| (miswired.wire, miswired.reason))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                ("c00".to_string(), "adders are only made of AND, OR and XOR"),
+                ("a01".to_string(), "adder gates have two inputs"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,464 @@
+//! Proving a circuit adds, rather than trying it on samples, by working out the Boolean function
+//! of the inputs that every wire computes.
+//!
+//! Functions are kept as reduced ordered binary decision diagrams, which are canonical for a fixed
+//! variable order: two wires compute the same function exactly when they end up as the same node.
+//! The inputs are ordered `x00, y00, x01, y01, ...` so the carries stay linear in size.
+
+use std::{collections::HashMap, fmt};
+
+use super::{
+    compiled::{CircuitError, CompiledCircuit},
+    Gate, GateOp,
+};
+
+pub type NodeId = usize;
+
+pub const FALSE: NodeId = 0;
+pub const TRUE: NodeId = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Node {
+    var: usize,
+    low: NodeId,
+    high: NodeId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Connective {
+    And,
+    Or,
+    Xor,
+}
+
+impl Connective {
+    fn apply(self, lhs: bool, rhs: bool) -> bool {
+        match self {
+            Connective::And => lhs && rhs,
+            Connective::Or => lhs || rhs,
+            Connective::Xor => lhs ^ rhs,
+        }
+    }
+}
+
+/// A shared pool of decision diagrams, each one named by the [`NodeId`] of its root
+#[derive(Debug, Clone)]
+pub struct Bdd {
+    nodes: Vec<Node>,
+    unique: HashMap<Node, NodeId>,
+    computed: HashMap<(Connective, NodeId, NodeId), NodeId>,
+}
+
+impl Default for Bdd {
+    fn default() -> Bdd {
+        // The terminals come after every variable
+        let terminal = |value| Node {
+            var: usize::MAX,
+            low: value,
+            high: value,
+        };
+        Bdd {
+            nodes: vec![terminal(FALSE), terminal(TRUE)],
+            unique: HashMap::new(),
+            computed: HashMap::new(),
+        }
+    }
+}
+
+impl Bdd {
+    pub fn new() -> Bdd {
+        Bdd::default()
+    }
+
+    /// Number of nodes made so far, including the terminals
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node(&mut self, var: usize, low: NodeId, high: NodeId) -> NodeId {
+        if low == high {
+            return low;
+        }
+
+        let node = Node { var, low, high };
+        if let Some(&id) = self.unique.get(&node) {
+            return id;
+        }
+        self.nodes.push(node);
+        self.unique.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    /// The function which is just variable `var`
+    pub fn var(&mut self, var: usize) -> NodeId {
+        self.node(var, FALSE, TRUE)
+    }
+
+    fn apply(&mut self, connective: Connective, lhs: NodeId, rhs: NodeId) -> NodeId {
+        if lhs <= TRUE && rhs <= TRUE {
+            return connective.apply(lhs == TRUE, rhs == TRUE) as NodeId;
+        }
+
+        // All three connectives are commutative
+        let key = (connective, lhs.min(rhs), lhs.max(rhs));
+        if let Some(&id) = self.computed.get(&key) {
+            return id;
+        }
+
+        let (l, r) = (self.nodes[lhs], self.nodes[rhs]);
+        let var = l.var.min(r.var);
+        let cofactors = |id, node: Node| match node.var == var {
+            true => (node.low, node.high),
+            false => (id, id),
+        };
+        let (l_low, l_high) = cofactors(lhs, l);
+        let (r_low, r_high) = cofactors(rhs, r);
+
+        let low = self.apply(connective, l_low, r_low);
+        let high = self.apply(connective, l_high, r_high);
+        let id = self.node(var, low, high);
+        self.computed.insert(key, id);
+        id
+    }
+
+    pub fn and(&mut self, lhs: NodeId, rhs: NodeId) -> NodeId {
+        self.apply(Connective::And, lhs, rhs)
+    }
+
+    pub fn or(&mut self, lhs: NodeId, rhs: NodeId) -> NodeId {
+        self.apply(Connective::Or, lhs, rhs)
+    }
+
+    pub fn xor(&mut self, lhs: NodeId, rhs: NodeId) -> NodeId {
+        self.apply(Connective::Xor, lhs, rhs)
+    }
+
+    pub fn not(&mut self, id: NodeId) -> NodeId {
+        self.xor(id, TRUE)
+    }
+
+    /// The function a gate computes from its inputs' functions
+    pub fn gate(&mut self, op: GateOp, inputs: &[NodeId]) -> NodeId {
+        let mut fold = |connective, init| {
+            inputs
+                .iter()
+                .fold(init, |acc, &input| self.apply(connective, acc, input))
+        };
+
+        match op {
+            GateOp::And => fold(Connective::And, TRUE),
+            GateOp::Or => fold(Connective::Or, FALSE),
+            GateOp::Xor => fold(Connective::Xor, FALSE),
+            GateOp::Not | GateOp::Nor => {
+                let or = fold(Connective::Or, FALSE);
+                self.not(or)
+            }
+            GateOp::Nand => {
+                let and = fold(Connective::And, TRUE);
+                self.not(and)
+            }
+            GateOp::Xnor => {
+                let xor = fold(Connective::Xor, FALSE);
+                self.not(xor)
+            }
+            GateOp::Const(value) => value as NodeId,
+        }
+    }
+
+    /// The value of `id` with each variable set by `assignment`
+    pub fn eval(&self, mut id: NodeId, assignment: impl Fn(usize) -> bool) -> bool {
+        while id > TRUE {
+            let node = self.nodes[id];
+            id = if assignment(node.var) {
+                node.high
+            } else {
+                node.low
+            };
+        }
+
+        id == TRUE
+    }
+
+    /// Variables to set to make `id` true, preferring false, with any not mentioned free to be
+    /// anything. `None` when `id` is never true.
+    pub fn satisfy(&self, mut id: NodeId) -> Option<Vec<(usize, bool)>> {
+        if id == FALSE {
+            return None;
+        }
+
+        // Every node other than FALSE has a path to TRUE, so this never gets stuck
+        let mut assignment = Vec::new();
+        while id > TRUE {
+            let node = self.nodes[id];
+            let value = node.low == FALSE;
+            assignment.push((node.var, value));
+            id = if value { node.high } else { node.low };
+        }
+
+        Some(assignment)
+    }
+}
+
+/// The lowest output bit which doesn't compute `x + y`, with operands which show it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    pub bit: usize,
+    pub x: u64,
+    pub y: u64,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "z{:02} isn't bit {} of x + y, e.g. x={:#b} y={:#b}",
+            self.bit, self.bit, self.x, self.y
+        )
+    }
+}
+
+/// The sum bits of two `bits` wide numbers, the last one being the carry out
+pub fn adder_outputs(bdd: &mut Bdd, bits: usize) -> Vec<NodeId> {
+    let mut outputs = Vec::with_capacity(bits + 1);
+    let mut carry = FALSE;
+    for i in 0..bits {
+        let (x, y) = (bdd.var(2 * i), bdd.var(2 * i + 1));
+        let half = bdd.xor(x, y);
+        outputs.push(bdd.xor(half, carry));
+
+        let generate = bdd.and(x, y);
+        let propagate = bdd.and(half, carry);
+        carry = bdd.or(generate, propagate);
+    }
+    outputs.push(carry);
+
+    outputs
+}
+
+/// Every wire of a circuit as a function of its `x` and `y` inputs. Bit `i` of `x` is variable
+/// `2i` and bit `i` of `y` is `2i + 1`. Other wires no gate drives are off, as they are in
+/// [`CompiledCircuit::add`].
+#[derive(Debug, Clone)]
+pub struct SymbolicCircuit {
+    pub bdd: Bdd,
+    circuit: CompiledCircuit,
+    /// Indexed by wire
+    functions: Vec<NodeId>,
+    bits: usize,
+}
+
+impl SymbolicCircuit {
+    pub fn new(gates: &[Gate]) -> Result<SymbolicCircuit, CircuitError> {
+        let circuit = CompiledCircuit::new(gates)?;
+        let mut bdd = Bdd::new();
+        let mut functions = vec![FALSE; circuit.wire_count()];
+
+        let (x, y) = (circuit.bus('x'), circuit.bus('y'));
+        for (offset, bus) in [(0, &x), (1, &y)] {
+            for (i, id) in bus.iter().enumerate() {
+                if let Some(id) = id {
+                    functions[*id] = bdd.var(2 * i + offset);
+                }
+            }
+        }
+
+        for gate in &circuit.gates {
+            let inputs: Vec<_> = gate.inputs.iter().map(|&input| functions[input]).collect();
+            functions[gate.output] = bdd.gate(gate.op, &inputs);
+        }
+
+        Ok(SymbolicCircuit {
+            bdd,
+            bits: x.len().max(y.len()),
+            circuit,
+            functions,
+        })
+    }
+
+    /// The function `wire` computes
+    pub fn function(&self, wire: &str) -> Option<NodeId> {
+        self.circuit.id(wire).map(|id| self.functions[id])
+    }
+
+    /// Compares every `z` wire with the matching bit of `x + y`, returning each one which differs
+    /// for some input. A missing `z` wire counts as always being 0. The operands in a [`Mismatch`]
+    /// only cover the low 64 bits.
+    pub fn mismatches(&mut self) -> Vec<Mismatch> {
+        self.differences()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(bit, difference)| self.example(bit, difference))
+            .collect()
+    }
+
+    /// The output bits which can be the lowest wrong one: wrong for some input while every bit
+    /// below comes out right, so the bit is wrong even though the carry into it is right. Unlike
+    /// [`SymbolicCircuit::mismatches`] this leaves out the bits above a bad carry, which are only
+    /// wrong because of it.
+    pub fn lowest_mismatches(&mut self) -> Vec<Mismatch> {
+        let mut below_right = TRUE;
+        let mut mismatches = Vec::new();
+        for (bit, difference) in self.differences().into_iter().enumerate() {
+            let lowest = self.bdd.and(below_right, difference);
+            mismatches.extend(self.example(bit, lowest));
+
+            let right = self.bdd.not(difference);
+            below_right = self.bdd.and(below_right, right);
+        }
+
+        mismatches
+    }
+
+    /// For each output bit, the inputs where it differs from `x + y`
+    fn differences(&mut self) -> Vec<NodeId> {
+        let expected = adder_outputs(&mut self.bdd, self.bits);
+        let outputs = self.circuit.bus('z');
+
+        (0..expected.len().max(outputs.len()))
+            .map(|bit| {
+                let actual = outputs
+                    .get(bit)
+                    .copied()
+                    .flatten()
+                    .map_or(FALSE, |id| self.functions[id]);
+                let expected = expected.get(bit).copied().unwrap_or(FALSE);
+                self.bdd.xor(actual, expected)
+            })
+            .collect()
+    }
+
+    /// Operands for which `bit` goes wrong, if `wrong` can happen at all
+    fn example(&self, bit: usize, wrong: NodeId) -> Option<Mismatch> {
+        let (mut x, mut y) = (0, 0);
+        for (var, value) in self.bdd.satisfy(wrong)? {
+            if value && var / 2 < 64 {
+                match var % 2 {
+                    0 => x |= 1 << (var / 2),
+                    _ => y |= 1 << (var / 2),
+                }
+            }
+        }
+
+        Some(Mismatch { bit, x, y })
+    }
+
+    /// The lowest of the [`SymbolicCircuit::mismatches`], `None` meaning the circuit adds
+    /// correctly for every input
+    pub fn check_addition(&mut self) -> Option<Mismatch> {
+        self.mismatches().into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{
+        adder,
+        fixtures::{gates, ripple_adder},
+    };
+    use super::*;
+
+    #[test]
+    fn diagrams_are_canonical() {
+        let mut bdd = Bdd::new();
+        let (a, b) = (bdd.var(0), bdd.var(1));
+
+        // De Morgan
+        let nand = bdd.gate(GateOp::Nand, &[a, b]);
+        let (not_a, not_b) = (bdd.not(a), bdd.not(b));
+        assert_eq!(bdd.or(not_a, not_b), nand);
+
+        assert_eq!(bdd.xor(a, a), FALSE);
+        assert_eq!(bdd.or(a, not_a), TRUE);
+        assert_eq!(bdd.gate(GateOp::Xnor, &[a, b, a]), not_b);
+        assert!(bdd.eval(nand, |var| var == 0));
+        assert!(!bdd.eval(nand, |_| true));
+
+        assert_eq!(bdd.satisfy(FALSE), None);
+        let and = bdd.and(a, not_b);
+        assert_eq!(bdd.satisfy(and), Some(vec![(0, true), (1, false)]));
+    }
+
+    #[test]
+    fn proves_adders() {
+        let mut circuit = SymbolicCircuit::new(&ripple_adder(45)).unwrap();
+        assert_eq!(circuit.check_addition(), None);
+        // Each carry only grows with the bits below it, where a bad variable order would make it
+        // exponential
+        assert!(circuit.bdd.node_count() < 20_000);
+
+        // The same adder bit made of other gates
+        let gates = gates(
+            "x00 XNOR y00 -> n\nNOT n -> z00\nNOT x00 -> nx\nNOT y00 -> ny\nnx NOR ny -> z01",
+        );
+        assert_eq!(SymbolicCircuit::new(&gates).unwrap().check_addition(), None);
+    }
+
+    #[test]
+    fn finds_the_first_wrong_bit() {
+        let gates = adder::swap_outputs(
+            &ripple_adder(45),
+            &[
+                ("z30".to_string(), "p30".to_string()),
+                ("s12".to_string(), "a12".to_string()),
+            ],
+        );
+        let mismatch = SymbolicCircuit::new(&gates)
+            .unwrap()
+            .check_addition()
+            .unwrap();
+        assert_eq!(mismatch.bit, 12);
+
+        let sum = CompiledCircuit::new(&gates)
+            .unwrap()
+            .add(mismatch.x, mismatch.y);
+        assert_ne!(sum >> 12 & 1, (mismatch.x + mismatch.y) >> 12 & 1);
+    }
+
+    #[test]
+    fn leaves_out_bits_after_a_bad_carry() {
+        let gates = adder::swap_outputs(
+            &ripple_adder(45),
+            &[
+                ("z30".to_string(), "p30".to_string()),
+                ("s12".to_string(), "a12".to_string()),
+            ],
+        );
+        let mut circuit = SymbolicCircuit::new(&gates).unwrap();
+        assert!(circuit.mismatches().len() > 2);
+
+        let lowest = circuit.lowest_mismatches();
+        assert_eq!(
+            lowest
+                .iter()
+                .map(|mismatch| mismatch.bit)
+                .collect::<Vec<_>>(),
+            vec![12, 30]
+        );
+        // Everything below the bit is right in the example
+        let high = lowest[1];
+        let sum = CompiledCircuit::new(&gates).unwrap().add(high.x, high.y);
+        let low_bits = (1 << 30) - 1;
+        assert_eq!(sum & low_bits, (high.x + high.y) & low_bits);
+        assert_ne!(sum >> 30 & 1, (high.x + high.y) >> 30 & 1);
+    }
+
+    #[test]
+    fn reports_missing_outputs() {
+        let gates = ripple_adder(4)
+            .into_iter()
+            .filter(|gate| gate.output != "z02")
+            .collect::<Vec<_>>();
+        let mismatch = SymbolicCircuit::new(&gates)
+            .unwrap()
+            .check_addition()
+            .unwrap();
+        assert_eq!(mismatch.bit, 2);
+        assert_eq!(
+            mismatch.to_string(),
+            format!(
+                "z02 isn't bit 2 of x + y, e.g. x={:#b} y={:#b}",
+                mismatch.x, mismatch.y
+            )
+        );
+    }
+}
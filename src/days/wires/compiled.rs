@@ -0,0 +1,300 @@
+//! Circuits sorted once so they can be evaluated in a single pass.
+//!
+//! Wire names are interned to [`WireId`]s and the gates are put in topological order, so every
+//! gate's inputs are known by the time it's evaluated. Evaluating is then one walk over a `Vec`
+//! instead of repeatedly sweeping the gates until the outputs settle.
+
+use std::{collections::HashMap, fmt};
+
+use super::{CircuitState, Gate, GateOp};
+
+pub type WireId = usize;
+
+/// Why a set of gates can't be evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitError {
+    /// The wires around a loop of gates which feeds back into itself
+    Cycle(Vec<String>),
+    /// More than one gate writes to this wire
+    MultipleDrivers(String),
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::Cycle(wires) => write!(f, "cycle through {}", wires.join(", ")),
+            CircuitError::MultipleDrivers(wire) => {
+                write!(f, "{wire} is the output of more than one gate")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledGate {
+    pub op: GateOp,
+    pub inputs: Vec<WireId>,
+    pub output: WireId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledCircuit {
+    names: Vec<String>,
+    ids: HashMap<String, WireId>,
+    /// Wires named by a letter and a bit number, by letter and then bit. Adding reads and writes
+    /// these over and over, so they're only worked out once.
+    buses: HashMap<char, Vec<Option<WireId>>>,
+    /// In topological order
+    pub gates: Vec<CompiledGate>,
+}
+
+impl CompiledCircuit {
+    pub fn new(gates: &[Gate]) -> Result<CompiledCircuit, CircuitError> {
+        let mut names = Vec::new();
+        let mut ids = HashMap::new();
+        let mut intern = |name: &String| {
+            *ids.entry(name.clone()).or_insert_with(|| {
+                names.push(name.clone());
+                names.len() - 1
+            })
+        };
+
+        let compiled: Vec<_> = gates
+            .iter()
+            .map(|gate| CompiledGate {
+                op: gate.op,
+                inputs: gate.inputs.iter().map(&mut intern).collect(),
+                output: intern(&gate.output),
+            })
+            .collect();
+
+        let mut driver = vec![None; names.len()];
+        for (i, gate) in compiled.iter().enumerate() {
+            if driver[gate.output].replace(i).is_some() {
+                return Err(CircuitError::MultipleDrivers(names[gate.output].clone()));
+            }
+        }
+
+        // Kahn's algorithm, counting how many of each gate's inputs are still to be computed
+        let mut readers = vec![Vec::new(); names.len()];
+        let mut waiting = vec![0; compiled.len()];
+        let mut ready = Vec::new();
+        for (i, gate) in compiled.iter().enumerate() {
+            for &input in &gate.inputs {
+                if driver[input].is_some() {
+                    readers[input].push(i);
+                    waiting[i] += 1;
+                }
+            }
+            if waiting[i] == 0 {
+                ready.push(i);
+            }
+        }
+
+        let mut order = Vec::with_capacity(compiled.len());
+        while let Some(i) = ready.pop() {
+            order.push(compiled[i].clone());
+            for &reader in &readers[compiled[i].output] {
+                waiting[reader] -= 1;
+                if waiting[reader] == 0 {
+                    ready.push(reader);
+                }
+            }
+        }
+
+        if let Some(start) = (0..compiled.len()).find(|&i| waiting[i] > 0) {
+            // Every stuck gate waits on another stuck gate, so walking back through them has to
+            // come round to one we've already seen. Gates only downstream of the loop drop out.
+            let mut path = vec![start];
+            let mut gate = start;
+            let cycle = loop {
+                gate = compiled[gate]
+                    .inputs
+                    .iter()
+                    .find_map(|&input| driver[input].filter(|&i| waiting[i] > 0))
+                    .expect("stuck gates wait on another stuck gate");
+                if let Some(seen) = path.iter().position(|&i| i == gate) {
+                    break path.split_off(seen);
+                }
+                path.push(gate);
+            };
+
+            let mut wires: Vec<_> = cycle
+                .into_iter()
+                .map(|i| names[compiled[i].output].clone())
+                .collect();
+            wires.sort();
+            return Err(CircuitError::Cycle(wires));
+        }
+
+        let mut buses: HashMap<char, Vec<Option<WireId>>> = HashMap::new();
+        for (id, name) in names.iter().enumerate() {
+            let mut chars = name.chars();
+            let Some(prefix) = chars.next() else {
+                continue;
+            };
+            let Ok(index) = chars.as_str().parse::<usize>() else {
+                continue;
+            };
+
+            let bits = buses.entry(prefix).or_default();
+            if bits.len() <= index {
+                bits.resize(index + 1, None);
+            }
+            bits[index] = Some(id);
+        }
+
+        Ok(CompiledCircuit {
+            names,
+            ids,
+            buses,
+            gates: order,
+        })
+    }
+
+    pub fn wire_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn id(&self, name: &str) -> Option<WireId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: WireId) -> &str {
+        &self.names[id]
+    }
+
+    /// The wires named `prefix` followed by a number, lowest bit first. Missing bits in the
+    /// middle of a bus are left as `None`.
+    pub fn bus(&self, prefix: char) -> &[Option<WireId>] {
+        self.buses.get(&prefix).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every wire's value, indexed by [`WireId`]. Wires not driven by a gate take their value from
+    /// `inputs`, or are off when they aren't in it.
+    pub fn evaluate(&self, inputs: &CircuitState) -> Vec<bool> {
+        let mut values = vec![false; self.names.len()];
+        for (name, value) in inputs {
+            if let Some(id) = self.id(name) {
+                values[id] = *value;
+            }
+        }
+
+        self.propagate(&mut values);
+        values
+    }
+
+    /// Runs every gate over `values` in order
+    pub fn propagate(&self, values: &mut [bool]) {
+        for gate in &self.gates {
+            values[gate.output] = gate.op.eval(gate.inputs.iter().map(|&input| values[input]));
+        }
+    }
+
+    /// Puts the low bits of `value` on the `prefix` bus
+    pub fn set_bus(&self, prefix: char, value: u64, values: &mut [bool]) {
+        for (i, id) in self.bus(prefix).iter().enumerate() {
+            if let Some(id) = *id {
+                values[id] = i < 64 && (value >> i) & 1 == 1;
+            }
+        }
+    }
+
+    /// The number on the `prefix` bus, which has to fit in 64 bits
+    pub fn read_bus(&self, prefix: char, values: &[bool]) -> u64 {
+        self.bus(prefix)
+            .iter()
+            .enumerate()
+            .filter_map(|(i, id)| id.and_then(|id| (values[id] as u64).checked_shl(i as u32)))
+            .sum()
+    }
+
+    /// Puts `x` and `y` on their buses and reads back `z`
+    pub fn add(&self, x: u64, y: u64) -> u64 {
+        let mut values = vec![false; self.names.len()];
+        self.set_bus('x', x, &mut values);
+        self.set_bus('y', y, &mut values);
+        self.propagate(&mut values);
+
+        self.read_bus('z', &values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::fixtures::gates;
+    use super::*;
+
+    #[test]
+    fn evaluates_in_one_pass() {
+        // Listed backwards so the gates have to be reordered
+        let circuit = CompiledCircuit::new(&gates(
+            "c AND x02 -> z02
+            y01 XOR x01 -> b
+            b XOR a -> z01
+            x00 AND y00 -> a
+            x00 XOR y00 -> z00
+            b AND a -> c",
+        ))
+        .unwrap();
+
+        assert_eq!(circuit.bus('z').len(), 3);
+        assert_eq!(circuit.bus('y'), vec![circuit.id("y00"), circuit.id("y01")]);
+        assert_eq!(circuit.add(0b01, 0b01), 0b010);
+        assert_eq!(circuit.add(0b11, 0b00), 0b011);
+        assert_eq!(circuit.add(0b111, 0b001), 0b100);
+    }
+
+    #[test]
+    fn reports_cycles() {
+        // z00 is stuck behind the loop but isn't part of it
+        let error = CompiledCircuit::new(&gates(
+            "a XOR b -> z00
+            x00 AND b -> a
+            a OR y00 -> b",
+        ))
+        .unwrap_err();
+        assert_eq!(error, CircuitError::Cycle(vec!["a".into(), "b".into()]));
+        assert_eq!(error.to_string(), "cycle through a, b");
+
+        assert_eq!(
+            CompiledCircuit::new(&gates("x00 AND y00 -> z00\nx00 OR y00 -> z00")),
+            Err(CircuitError::MultipleDrivers("z00".into()))
+        );
+    }
+
+    #[test]
+    fn handles_wide_buses() {
+        // z63 is x63 AND y63, which only works if all 64 bits make it through
+        let circuit = CompiledCircuit::new(&gates("x63 AND y63 -> z63")).unwrap();
+        assert_eq!(circuit.add(1 << 63, 1 << 63), 1 << 63);
+        assert_eq!(circuit.add(1 << 63, 0), 0);
+    }
+
+    #[test]
+    fn evaluates_general_gates() {
+        // A 3 input majority vote built from NANDs, plus a constant
+        let circuit = CompiledCircuit::new(&gates(
+            "x00 NAND y00 -> a
+            x00 NAND x01 -> b
+            y00 NAND x01 -> c
+            a NAND b NAND c -> z00
+            1 -> one
+            one XNOR z00 -> z01
+            NOT one -> z02",
+        ))
+        .unwrap();
+
+        for (x, y, majority) in [
+            (0b00, 0, 0),
+            (0b01, 0, 0),
+            (0b01, 1, 1),
+            (0b10, 1, 1),
+            (0b11, 0, 1),
+        ] {
+            assert_eq!(circuit.add(x, y), majority | majority << 1);
+        }
+    }
+}
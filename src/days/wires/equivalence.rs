@@ -0,0 +1,326 @@
+//! Testing a circuit against the arithmetic it's supposed to do.
+//!
+//! [`EquivalenceTest`] drives a [`CompiledCircuit`] with edge cases (zero, all ones, single bits,
+//! carries running across every bit) plus seeded random operands, or every pair of operands when
+//! the inputs are small enough. Failures are grouped by the lowest output bit which comes out
+//! wrong, and each group is shrunk down to the smallest operands which still show it.
+
+use std::fmt;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::compiled::CompiledCircuit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counterexample {
+    pub x: u64,
+    pub y: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl Counterexample {
+    /// The lowest bit of the output which is wrong
+    pub fn lowest_bit(&self) -> usize {
+        (self.expected ^ self.actual).trailing_zeros() as usize
+    }
+
+    /// Number of bits set across both operands, smaller examples are easier to follow
+    fn size(&self) -> (u32, u64, u64) {
+        (self.x.count_ones() + self.y.count_ones(), self.x, self.y)
+    }
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "x={:#b} y={:#b} gave {:#b}, expected {:#b}",
+            self.x, self.y, self.actual, self.expected
+        )
+    }
+}
+
+/// Every failure whose lowest wrong bit is `bit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    pub bit: usize,
+    pub failures: usize,
+    /// The smallest operands found which still get this bit wrong
+    pub example: Counterexample,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EquivalenceReport {
+    pub cases: usize,
+    /// Sorted by bit
+    pub faults: Vec<Fault>,
+}
+
+impl EquivalenceReport {
+    pub fn is_equivalent(&self) -> bool {
+        self.faults.is_empty()
+    }
+
+    pub fn lowest_failing_bit(&self) -> Option<usize> {
+        self.faults.first().map(|fault| fault.bit)
+    }
+}
+
+impl fmt::Display for EquivalenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_equivalent() {
+            return writeln!(f, "all {} cases passed", self.cases);
+        }
+
+        for fault in &self.faults {
+            writeln!(
+                f,
+                "bit {:02}: {} of {} cases wrong, e.g. {}",
+                fault.bit, fault.failures, self.cases, fault.example
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn mask(bits: usize) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+/// How hard to try when checking a circuit
+#[derive(Debug, Clone)]
+pub struct EquivalenceTest {
+    random_cases: usize,
+    seed: u64,
+    /// Try every pair of operands when x and y together are at most this many bits
+    exhaustive_bits: usize,
+}
+
+impl Default for EquivalenceTest {
+    fn default() -> EquivalenceTest {
+        EquivalenceTest {
+            random_cases: 1000,
+            seed: 24,
+            exhaustive_bits: 16,
+        }
+    }
+}
+
+impl EquivalenceTest {
+    pub fn new() -> EquivalenceTest {
+        EquivalenceTest::default()
+    }
+
+    pub fn with_random_cases(mut self, cases: usize) -> EquivalenceTest {
+        self.random_cases = cases;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> EquivalenceTest {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_exhaustive_limit(mut self, bits: usize) -> EquivalenceTest {
+        self.exhaustive_bits = bits;
+        self
+    }
+
+    /// The operands to try for inputs of the given widths
+    pub fn operands(&self, x_bits: usize, y_bits: usize) -> Vec<(u64, u64)> {
+        let (x_mask, y_mask) = (mask(x_bits), mask(y_bits));
+
+        if x_bits + y_bits <= self.exhaustive_bits {
+            return (0..=x_mask)
+                .flat_map(|x| (0..=y_mask).map(move |y| (x, y)))
+                .collect();
+        }
+
+        let width = x_bits.max(y_bits).min(64);
+        let mut operands = vec![(0, 0), (u64::MAX, 0), (0, u64::MAX), (u64::MAX, u64::MAX)];
+        // 0101... and 1010... so every bit sees both values next to both values
+        let alternating = 0x5555_5555_5555_5555;
+        operands.extend([(alternating, !alternating), (alternating, alternating)]);
+        for i in 0..width {
+            let bit = 1 << i;
+            let below = bit - 1;
+            operands.extend([
+                (bit, 0),
+                (0, bit),
+                (bit, bit),
+                // A carry from bit 0 which has to ripple all the way up to bit i
+                (below, 1),
+                (1, below),
+                (below | bit, bit),
+            ]);
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        operands.extend((0..self.random_cases).map(|_| (rng.gen(), rng.gen())));
+
+        operands
+            .into_iter()
+            .map(|(x, y)| (x & x_mask, y & y_mask))
+            .collect()
+    }
+
+    /// Checks `circuit` computes `expected` of its `x` and `y` inputs on its `z` outputs
+    pub fn check(
+        &self,
+        circuit: &CompiledCircuit,
+        expected: impl Fn(u64, u64) -> u64,
+    ) -> EquivalenceReport {
+        let z_mask = mask(circuit.bus('z').len());
+        let run = |x, y| Counterexample {
+            x,
+            y,
+            expected: expected(x, y) & z_mask,
+            actual: circuit.add(x, y),
+        };
+
+        let operands = self.operands(circuit.bus('x').len(), circuit.bus('y').len());
+        let mut faults: Vec<Fault> = Vec::new();
+        for &(x, y) in &operands {
+            let case = run(x, y);
+            if case.expected == case.actual {
+                continue;
+            }
+
+            let bit = case.lowest_bit();
+            let example = shrink(case, run);
+            match faults.iter_mut().find(|fault| fault.bit == bit) {
+                Some(fault) => {
+                    fault.failures += 1;
+                    if example.size() < fault.example.size() {
+                        fault.example = example;
+                    }
+                }
+                None => faults.push(Fault {
+                    bit,
+                    failures: 1,
+                    example,
+                }),
+            }
+        }
+        faults.sort_by_key(|fault| fault.bit);
+
+        EquivalenceReport {
+            cases: operands.len(),
+            faults,
+        }
+    }
+
+    /// [`EquivalenceTest::check`] against `x + y`
+    pub fn check_addition(&self, circuit: &CompiledCircuit) -> EquivalenceReport {
+        self.check(circuit, |x, y| x.wrapping_add(y))
+    }
+}
+
+/// Clears operand bits, highest first, for as long as the same output bit is still the lowest
+/// one wrong
+fn shrink(mut case: Counterexample, run: impl Fn(u64, u64) -> Counterexample) -> Counterexample {
+    let bit = case.lowest_bit();
+
+    for i in (0..64).rev() {
+        for clear_x in [true, false] {
+            let (x, y) = match clear_x {
+                true => (case.x & !(1 << i), case.y),
+                false => (case.x, case.y & !(1 << i)),
+            };
+            if (x, y) == (case.x, case.y) {
+                continue;
+            }
+
+            let smaller = run(x, y);
+            if smaller.expected != smaller.actual && smaller.lowest_bit() == bit {
+                case = smaller;
+            }
+        }
+    }
+
+    case
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{
+        adder,
+        fixtures::{gates, TWO_BIT_ADDER},
+    };
+    use super::*;
+
+    #[test]
+    fn passes_working_circuits() {
+        let circuit = CompiledCircuit::new(&gates(TWO_BIT_ADDER)).unwrap();
+        let report = EquivalenceTest::new().check_addition(&circuit);
+
+        // Small enough to try all 16 pairs
+        assert_eq!(report.cases, 16);
+        assert!(report.is_equivalent());
+        assert_eq!(report.to_string(), "all 16 cases passed\n");
+    }
+
+    #[test]
+    fn finds_the_lowest_failing_bit() {
+        // The carry out of bit 0 is an OR instead of an AND
+        let broken = TWO_BIT_ADDER.replace("x00 AND y00", "x00 OR y00");
+        let circuit = CompiledCircuit::new(&gates(&broken)).unwrap();
+        let report = EquivalenceTest::new().check_addition(&circuit);
+
+        assert_eq!(report.lowest_failing_bit(), Some(1));
+        assert_eq!(
+            report.faults[0].example,
+            Counterexample {
+                x: 0,
+                y: 1,
+                expected: 1,
+                actual: 3
+            }
+        );
+        assert_eq!(
+            report.to_string(),
+            "bit 01: 8 of 16 cases wrong, e.g. x=0b0 y=0b1 gave 0b11, expected 0b1\n"
+        );
+    }
+
+    #[test]
+    fn checks_wide_circuits_with_other_functions() {
+        // Bits wired straight through as AND gates
+        let text: Vec<_> = (0..40)
+            .map(|i| format!("x{i:02} AND y{i:02} -> z{i:02}"))
+            .collect();
+        let circuit = CompiledCircuit::new(&gates(&text.join("\n"))).unwrap();
+
+        let test = EquivalenceTest::new().with_random_cases(200).with_seed(7);
+        assert!(test.check(&circuit, |x, y| x & y).is_equivalent());
+
+        let report = test.check_addition(&circuit);
+        assert_eq!(report.lowest_failing_bit(), Some(0));
+        assert_eq!(
+            (report.faults[0].example.x, report.faults[0].example.y),
+            (0, 1)
+        );
+    }
+
+    #[test]
+    fn shrinks_swapped_adders() {
+        let gates = adder::swap_outputs(
+            &gates(TWO_BIT_ADDER),
+            &[("z01".to_string(), "p01".to_string())],
+        );
+        let circuit = CompiledCircuit::new(&gates).unwrap();
+        let report = EquivalenceTest::new()
+            .with_exhaustive_limit(0)
+            .check_addition(&circuit);
+
+        assert!(report.cases > 16);
+        assert_eq!(report.lowest_failing_bit(), Some(1));
+        let example = report.faults[0].example;
+        assert_eq!(example.x.count_ones() + example.y.count_ones(), 1);
+    }
+}
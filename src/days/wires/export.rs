@@ -0,0 +1,286 @@
+//! Writing circuits out for other tools: Graphviz to look at them, Verilog to simulate them.
+//!
+//! ```text
+//! dot -Tsvg circuit.dot > circuit.svg
+//! iverilog circuit.v
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+};
+
+use super::{Gate, GateOp};
+
+/// The bit number of a bus wire like `x07`
+fn bus_index(wire: &str) -> Option<(char, usize)> {
+    let mut chars = wire.chars();
+    let prefix = chars.next().filter(|c| matches!(c, 'x' | 'y' | 'z'))?;
+    let digits = chars.as_str();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some((prefix, digits.parse().ok()?))
+}
+
+/// Which bit of the adder each wire belongs to, the highest input bit it depends on
+fn bit_indices(gates: &[Gate]) -> HashMap<&str, usize> {
+    let mut bits: HashMap<&str, usize> = HashMap::new();
+    for wire in gates
+        .iter()
+        .flat_map(|g| g.inputs.iter().chain([&g.output]))
+    {
+        if let Some((_, index)) = bus_index(wire) {
+            bits.insert(wire, index);
+        }
+    }
+
+    // Relax until nothing changes rather than sorting, so circuits with cycles still export
+    for _ in 0..gates.len() {
+        let mut changed = false;
+        for gate in gates {
+            let Some(bit) = gate
+                .inputs
+                .iter()
+                .filter_map(|wire| bits.get(wire.as_str()).copied())
+                .max()
+            else {
+                continue;
+            };
+
+            if bus_index(&gate.output).is_none() && bits.get(gate.output.as_str()) < Some(&bit) {
+                bits.insert(&gate.output, bit);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    bits
+}
+
+fn color(op: GateOp) -> &'static str {
+    match op {
+        GateOp::And => "lightblue",
+        GateOp::Or => "palegreen",
+        GateOp::Xor => "gold",
+        GateOp::Not => "lightpink",
+        GateOp::Nand => "lightskyblue",
+        GateOp::Nor => "mediumseagreen",
+        GateOp::Xnor => "orange",
+        GateOp::Const(_) => "lightgrey",
+    }
+}
+
+/// A Graphviz digraph with a node per wire, grouped into a cluster per bit and coloured by the
+/// gate driving it. Wires in `highlight` are outlined in red.
+pub fn to_dot(gates: &[Gate], highlight: &HashSet<String>) -> String {
+    let bits = bit_indices(gates);
+    let drivers: HashMap<&str, GateOp> = gates.iter().map(|g| (g.output.as_str(), g.op)).collect();
+
+    let mut clusters: BTreeMap<Option<usize>, Vec<&str>> = BTreeMap::new();
+    let wires: HashSet<&str> = gates
+        .iter()
+        .flat_map(|g| g.inputs.iter().chain([&g.output]))
+        .map(String::as_str)
+        .collect();
+    for wire in wires {
+        clusters
+            .entry(bits.get(wire).copied())
+            .or_default()
+            .push(wire);
+    }
+
+    let mut dot = String::new();
+    writeln!(dot, "digraph circuit {{").unwrap();
+    writeln!(dot, "    rankdir=LR;").unwrap();
+    writeln!(dot, "    node [style=filled, fillcolor=white];").unwrap();
+
+    for (bit, mut wires) in clusters {
+        wires.sort();
+        let indent = match bit {
+            Some(bit) => {
+                writeln!(dot, "    subgraph cluster_{bit:02} {{").unwrap();
+                writeln!(dot, "        label=\"bit {bit:02}\";").unwrap();
+                "        "
+            }
+            None => "    ",
+        };
+
+        for wire in wires {
+            let mut attributes = match drivers.get(wire) {
+                Some(&op) => format!(
+                    "label=\"{wire}\\n{}\", shape=box, fillcolor={}",
+                    op.name(),
+                    color(op)
+                ),
+                None => "shape=ellipse".to_string(),
+            };
+            if highlight.contains(wire) {
+                attributes.push_str(", color=red, penwidth=3");
+            }
+            writeln!(dot, "{indent}\"{wire}\" [{attributes}];").unwrap();
+        }
+
+        if bit.is_some() {
+            writeln!(dot, "    }}").unwrap();
+        }
+    }
+
+    for gate in gates {
+        for input in &gate.inputs {
+            writeln!(dot, "    \"{input}\" -> \"{}\";", gate.output).unwrap();
+        }
+    }
+    writeln!(dot, "}}").unwrap();
+
+    dot
+}
+
+const KEYWORDS: &[&str] = &[
+    "and", "assign", "begin", "buf", "case", "end", "for", "if", "initial", "input", "module",
+    "nand", "nor", "not", "or", "output", "reg", "wire", "xnor", "xor",
+];
+
+/// How a wire is written in Verilog, bus wires become bits of the `x`, `y` and `z` ports
+fn verilog_name(wire: &str) -> String {
+    match bus_index(wire) {
+        Some((prefix, index)) => format!("{prefix}[{index}]"),
+        // Escaped identifiers end at whitespace
+        None if KEYWORDS.contains(&wire) => format!("\\{wire} "),
+        None => wire.to_string(),
+    }
+}
+
+/// A structural Verilog module with `x` and `y` inputs, a `z` output and one gate primitive per
+/// gate, or an `assign` for constants. Any other wires no gate drives become extra inputs.
+pub fn to_verilog(gates: &[Gate], module: &str) -> String {
+    let mut widths: BTreeMap<char, usize> = BTreeMap::new();
+    let mut internal = Vec::new();
+    let driven: HashSet<&str> = gates.iter().map(|g| g.output.as_str()).collect();
+    let mut extra_inputs = Vec::new();
+
+    for wire in gates
+        .iter()
+        .flat_map(|g| g.inputs.iter().chain([&g.output]))
+    {
+        match bus_index(wire) {
+            Some((prefix, index)) => {
+                let width = widths.entry(prefix).or_default();
+                *width = (*width).max(index + 1);
+            }
+            None if driven.contains(wire.as_str()) => internal.push(wire.as_str()),
+            None => extra_inputs.push(wire.as_str()),
+        }
+    }
+    internal.sort();
+    internal.dedup();
+    extra_inputs.sort();
+    extra_inputs.dedup();
+
+    let mut ports = Vec::new();
+    for (prefix, width) in &widths {
+        let direction = if *prefix == 'z' { "output" } else { "input" };
+        ports.push(format!("{direction} [{}:0] {prefix}", width - 1));
+    }
+    for wire in &extra_inputs {
+        ports.push(format!("input {}", verilog_name(wire)));
+    }
+
+    let mut verilog = String::new();
+    writeln!(verilog, "module {module} (").unwrap();
+    writeln!(verilog, "    {}", ports.join(",\n    ")).unwrap();
+    writeln!(verilog, ");").unwrap();
+    for wire in internal {
+        writeln!(verilog, "    wire {};", verilog_name(wire)).unwrap();
+    }
+    writeln!(verilog).unwrap();
+
+    for (i, gate) in gates.iter().enumerate() {
+        let output = verilog_name(&gate.output);
+        if let GateOp::Const(value) = gate.op {
+            writeln!(verilog, "    assign {output} = 1'b{};", value as u8).unwrap();
+            continue;
+        }
+
+        let primitive = gate.op.name().to_ascii_lowercase();
+        let terminals: Vec<_> = [output]
+            .into_iter()
+            .chain(gate.inputs.iter().map(|input| verilog_name(input)))
+            .collect();
+        writeln!(verilog, "    {primitive} g{i} ({});", terminals.join(", ")).unwrap();
+    }
+    writeln!(verilog, "endmodule").unwrap();
+
+    verilog
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::fixtures::gates;
+    use super::*;
+
+    const HALF_ADDERS: &str = "x00 XOR y00 -> z00
+x00 AND y00 -> and
+x01 XOR y01 -> abc
+abc XOR and -> z01
+abc AND and -> z02";
+
+    #[test]
+    fn writes_dot() {
+        let highlight = HashSet::from(["abc".to_string()]);
+        let dot = to_dot(&gates(HALF_ADDERS), &highlight);
+
+        assert!(dot.starts_with("digraph circuit {\n"));
+        assert!(dot.contains(
+            "    subgraph cluster_00 {\n        label=\"bit 00\";\n        \"and\" [label=\"and\\nAND\", shape=box, fillcolor=lightblue];\n"
+        ));
+        assert!(dot.contains(
+            "        \"abc\" [label=\"abc\\nXOR\", shape=box, fillcolor=gold, color=red, penwidth=3];\n"
+        ));
+        assert!(dot.contains("        \"x01\" [shape=ellipse];\n"));
+        assert!(dot.contains("    \"abc\" -> \"z02\";\n"));
+        assert_eq!(dot.matches("subgraph").count(), 3);
+    }
+
+    #[test]
+    fn writes_verilog() {
+        assert_eq!(
+            to_verilog(&gates(HALF_ADDERS), "adder"),
+            "module adder (
+    input [1:0] x,
+    input [1:0] y,
+    output [2:0] z
+);
+    wire abc;
+    wire \\and ;
+
+    xor g0 (z[0], x[0], y[0]);
+    and g1 (\\and , x[0], y[0]);
+    xor g2 (abc, x[1], y[1]);
+    xor g3 (z[1], abc, \\and );
+    and g4 (z[2], abc, \\and );
+endmodule
+"
+        );
+    }
+
+    #[test]
+    fn writes_general_gates() {
+        let gates = gates("NOT x00 -> z00\n1 -> one\nx00 NOR x01 NOR one -> z01");
+
+        let verilog = to_verilog(&gates, "misc");
+        assert!(verilog.contains("    wire one;\n"));
+        assert!(verilog.contains("    not g0 (z[0], x[0]);\n"));
+        assert!(verilog.contains("    assign one = 1'b1;\n"));
+        assert!(verilog.contains("    nor g2 (z[1], x[0], x[1], one);\n"));
+
+        let dot = to_dot(&gates, &HashSet::new());
+        assert!(dot.contains("\"one\" [label=\"one\\n1\", shape=box, fillcolor=lightgrey];"));
+        assert!(dot.contains("    \"one\" -> \"z01\";\n"));
+    }
+}
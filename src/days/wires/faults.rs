@@ -0,0 +1,286 @@
+//! Narrowing down which gates are to blame when an adder gets some bits wrong.
+//!
+//! Each `z` wire's cone of influence is every gate it depends on, found with [`search_gates`].
+//! A bit only counts as wrong when it can go wrong with the right carry coming in, since a bad
+//! carry makes every bit above it wrong as well and would drag all of their gates in with it.
+//! Gates which only appear in the cones of wrong bits are more suspicious than ones shared with
+//! bits which come out right. Suspects are ranked with the Ochiai score from spectrum based fault
+//! localisation, `failing / sqrt(wrong bits * (failing + passing))`, where `failing` and `passing`
+//! count the wrong and right cones a gate is in.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use super::{bdd::SymbolicCircuit, compiled::CircuitError, search_gates, Gate};
+
+/// How many suspects [`FaultReport`]'s table lists
+const SHOWN_SUSPECTS: usize = 10;
+
+/// An output bit which doesn't match `x + y` even with the right carry in, with the gates it
+/// depends on named by their outputs. Outputs which nothing drives have an empty cone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaultyBit {
+    pub bit: usize,
+    pub output: String,
+    /// Sorted, as are `unique` and `shared`
+    pub cone: Vec<String>,
+    /// In no correct bit's cone
+    pub unique: Vec<String>,
+    /// Also in some correct bit's cone
+    pub shared: Vec<String>,
+}
+
+/// A gate output which might be one of the crossed wires
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suspect {
+    pub wire: String,
+    /// Number of wrong bits whose cone includes this gate
+    pub failing: usize,
+    /// Number of correct bits whose cone includes this gate
+    pub passing: usize,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultReport {
+    /// Sorted by bit
+    pub faulty: Vec<FaultyBit>,
+    pub correct: usize,
+    /// Most suspicious first
+    pub suspects: Vec<Suspect>,
+}
+
+impl FaultReport {
+    /// Checks every output bit of `gates` against `x + y` and ranks the gates behind the wrong ones
+    pub fn new(gates: &[Gate]) -> Result<FaultReport, CircuitError> {
+        let wrong: HashSet<usize> = SymbolicCircuit::new(gates)?
+            .lowest_mismatches()
+            .into_iter()
+            .map(|mismatch| mismatch.bit)
+            .collect();
+
+        let driven: HashSet<&str> = gates.iter().map(|gate| gate.output.as_str()).collect();
+        let mut outputs: BTreeMap<usize, String> = driven
+            .iter()
+            .filter_map(|wire| Some((wire.strip_prefix('z')?.parse().ok()?, wire.to_string())))
+            .collect();
+        for &bit in &wrong {
+            outputs.entry(bit).or_insert_with(|| format!("z{bit:02}"));
+        }
+
+        let mut faulty = Vec::new();
+        let mut correct_cones: Vec<HashSet<String>> = Vec::new();
+        for (bit, output) in outputs {
+            let mut cone: Vec<String> = search_gates(output.clone(), gates, true)
+                .into_iter()
+                .filter(|wire| driven.contains(wire.as_str()))
+                .collect();
+            cone.sort();
+
+            match wrong.contains(&bit) {
+                true => faulty.push(FaultyBit {
+                    bit,
+                    output,
+                    cone,
+                    unique: Vec::new(),
+                    shared: Vec::new(),
+                }),
+                false => correct_cones.push(cone.into_iter().collect()),
+            }
+        }
+
+        let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+        for fault in &faulty {
+            for wire in &fault.cone {
+                counts.entry(wire.clone()).or_default().0 += 1;
+            }
+        }
+        for cone in &correct_cones {
+            for wire in cone {
+                if let Some((_, passing)) = counts.get_mut(wire) {
+                    *passing += 1;
+                }
+            }
+        }
+
+        for fault in &mut faulty {
+            let (shared, unique) = fault
+                .cone
+                .iter()
+                .cloned()
+                .partition(|wire| counts[wire].1 > 0);
+            fault.shared = shared;
+            fault.unique = unique;
+        }
+
+        let mut suspects: Vec<Suspect> = counts
+            .into_iter()
+            .map(|(wire, (failing, passing))| Suspect {
+                wire,
+                failing,
+                passing,
+                score: failing as f64 / ((faulty.len() * (failing + passing)) as f64).sqrt(),
+            })
+            .collect();
+        suspects.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.wire.cmp(&b.wire)));
+
+        Ok(FaultReport {
+            faulty,
+            correct: correct_cones.len(),
+            suspects,
+        })
+    }
+}
+
+impl fmt::Display for FaultReport {
+    /// A table of how many gates each wrong bit depends on, and one of the top suspects
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.faulty.is_empty() {
+            return writeln!(f, "all {} output bits are correct", self.correct);
+        }
+
+        writeln!(
+            f,
+            "{:<6} {:>5} {:>6} {:>6}",
+            "output", "cone", "shared", "unique"
+        )?;
+        for fault in &self.faulty {
+            writeln!(
+                f,
+                "{:<6} {:>5} {:>6} {:>6}",
+                fault.output,
+                fault.cone.len(),
+                fault.shared.len(),
+                fault.unique.len()
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<6} {:>7} {:>7} {:>6}",
+            "wire", "failing", "passing", "score"
+        )?;
+        for suspect in self.suspects.iter().take(SHOWN_SUSPECTS) {
+            writeln!(
+                f,
+                "{:<6} {:>7} {:>7} {:>6.3}",
+                suspect.wire, suspect.failing, suspect.passing, suspect.score
+            )?;
+        }
+        if self.suspects.len() > SHOWN_SUSPECTS {
+            writeln!(f, "... and {} more", self.suspects.len() - SHOWN_SUSPECTS)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{
+        adder,
+        fixtures::{gates, TWO_BIT_ADDER},
+    };
+    use super::*;
+
+    #[test]
+    fn passes_working_adders() {
+        let report = FaultReport::new(&gates(TWO_BIT_ADDER)).unwrap();
+        assert!(report.faulty.is_empty());
+        assert!(report.suspects.is_empty());
+        assert_eq!(report.to_string(), "all 3 output bits are correct\n");
+    }
+
+    #[test]
+    fn ranks_gates_only_wrong_bits_use() {
+        // The carry out of bit 1 uses an AND instead of an OR
+        let broken = TWO_BIT_ADDER.replace("a01 OR p01", "a01 AND p01");
+        let report = FaultReport::new(&gates(&broken)).unwrap();
+
+        assert_eq!(report.correct, 2);
+        assert_eq!(
+            report.faulty,
+            vec![FaultyBit {
+                bit: 2,
+                output: "z02".to_string(),
+                cone: vec!["a01", "c00", "p01", "s01", "z02"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                unique: vec!["a01".to_string(), "p01".to_string(), "z02".to_string()],
+                shared: vec!["c00".to_string(), "s01".to_string()],
+            }]
+        );
+
+        let ranked: Vec<_> = report
+            .suspects
+            .iter()
+            .map(|suspect| (suspect.wire.as_str(), suspect.failing, suspect.passing))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("a01", 1, 0),
+                ("p01", 1, 0),
+                ("z02", 1, 0),
+                ("c00", 1, 1),
+                ("s01", 1, 1)
+            ]
+        );
+        assert_eq!(report.suspects[0].score, 1.0);
+        assert!((report.suspects[3].score - 0.5f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn prints_a_table() {
+        // z01 gets the carry's AND gate, so only z01 goes wrong with the right carry in. z02 is
+        // still wrong whenever z01 is, but isn't to blame.
+        let gates = adder::swap_outputs(
+            &gates(TWO_BIT_ADDER),
+            &[("z01".to_string(), "p01".to_string())],
+        );
+        let report = FaultReport::new(&gates).unwrap();
+
+        assert_eq!(
+            report
+                .faulty
+                .iter()
+                .map(|fault| fault.bit)
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(report.suspects[0].wire, "z01");
+        assert_eq!(
+            report.to_string(),
+            "output  cone shared unique
+z01        3      2      1
+
+wire   failing passing  score
+z01          1       0  1.000
+c00          1       1  0.707
+s01          1       1  0.707
+"
+        );
+    }
+
+    #[test]
+    fn reports_missing_outputs() {
+        let broken = TWO_BIT_ADDER.replace("-> z01", "-> q01");
+        let report = FaultReport::new(&gates(&broken)).unwrap();
+
+        assert_eq!(
+            report.faulty,
+            vec![FaultyBit {
+                bit: 1,
+                output: "z01".to_string(),
+                cone: vec![],
+                unique: vec![],
+                shared: vec![],
+            }]
+        );
+        assert_eq!(report.correct, 2);
+    }
+}
@@ -0,0 +1,502 @@
+//! Day 24: Crossed Wires
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+pub mod adder;
+pub mod bdd;
+pub mod compiled;
+pub mod equivalence;
+pub mod export;
+pub mod faults;
+
+use crate::{
+    answer::Answer,
+    parse::{self, Line, ParseError, ParseResult},
+    solution::Solution,
+};
+
+use compiled::{CircuitError, CompiledCircuit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GateOp {
+    And,
+    Or,
+    Xor,
+    Not,
+    Nand,
+    Nor,
+    Xnor,
+    /// A wire tied to a fixed value, written `0 -> wire` or `1 -> wire`
+    Const(bool),
+}
+
+impl GateOp {
+    /// Binary operations as written between their inputs
+    fn from_name(name: &str) -> Option<GateOp> {
+        match name {
+            "AND" => Some(GateOp::And),
+            "OR" => Some(GateOp::Or),
+            "XOR" => Some(GateOp::Xor),
+            "NAND" => Some(GateOp::Nand),
+            "NOR" => Some(GateOp::Nor),
+            "XNOR" => Some(GateOp::Xnor),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GateOp::And => "AND",
+            GateOp::Or => "OR",
+            GateOp::Xor => "XOR",
+            GateOp::Not => "NOT",
+            GateOp::Nand => "NAND",
+            GateOp::Nor => "NOR",
+            GateOp::Xnor => "XNOR",
+            GateOp::Const(false) => "0",
+            GateOp::Const(true) => "1",
+        }
+    }
+
+    /// Applies the gate to any number of inputs, XOR being true when an odd number are on
+    pub fn eval(&self, inputs: impl IntoIterator<Item = bool>) -> bool {
+        let mut inputs = inputs.into_iter();
+        match self {
+            GateOp::And => inputs.all(|input| input),
+            GateOp::Or => inputs.any(|input| input),
+            GateOp::Xor => inputs.fold(false, |acc, input| acc ^ input),
+            // NOT only ever has one input, so this is just its negation
+            GateOp::Not | GateOp::Nor => !inputs.any(|input| input),
+            GateOp::Nand => !inputs.all(|input| input),
+            GateOp::Xnor => !inputs.fold(false, |acc, input| acc ^ input),
+            GateOp::Const(value) => *value,
+        }
+    }
+}
+
+/// A gate combining its `inputs` onto `output`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gate {
+    pub op: GateOp,
+    pub inputs: Vec<String>,
+    pub output: String,
+}
+
+impl Gate {
+    /// The usual two input gate from the puzzle
+    pub fn binary(lhs: &str, op: GateOp, rhs: &str, output: &str) -> Gate {
+        Gate {
+            op,
+            inputs: vec![lhs.to_string(), rhs.to_string()],
+            output: output.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Gate {
+    /// The gate as it's written in the input
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.op {
+            GateOp::Const(_) => write!(f, "{}", self.op.name())?,
+            GateOp::Not => write!(f, "NOT {}", self.inputs.join(" "))?,
+            op => write!(f, "{}", self.inputs.join(&format!(" {} ", op.name())))?,
+        }
+        write!(f, " -> {}", self.output)
+    }
+}
+
+/// The value of every wire which has one so far
+pub type CircuitState = HashMap<String, bool>;
+
+/// The initial wire values and the gates connecting them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Circuit {
+    pub inputs: CircuitState,
+    pub gates: Vec<Gate>,
+}
+
+impl Circuit {
+    /// The number on the `z` wires once the circuit settles with its own inputs
+    pub fn output(&self) -> Result<u64, CircuitError> {
+        let circuit = CompiledCircuit::new(&self.gates)?;
+        let values = circuit.evaluate(&self.inputs);
+
+        Ok(circuit.read_bus('z', &values))
+    }
+}
+
+impl FromStr for Circuit {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (inputs, gates) = parse_input(s)?;
+
+        Ok(Circuit { inputs, gates })
+    }
+}
+
+pub fn parse_input(puzzle_input: &str) -> ParseResult<(CircuitState, Vec<Gate>)> {
+    let [inputs, gates] = parse::sections_n(puzzle_input)?;
+
+    let inputs = inputs
+        .fields()?
+        .into_iter()
+        .map(|field| {
+            let value = match field.value.text {
+                "0" => false,
+                "1" => true,
+                value => {
+                    return Err(field
+                        .value
+                        .error(format!("'{value}' isn't a wire value, expected 0 or 1")))
+                }
+            };
+            Ok((field.key.to_string(), value))
+        })
+        .collect::<ParseResult<_>>()?;
+
+    let gates = gates
+        .lines()
+        .map(|line| parse_gate(&line))
+        .collect::<ParseResult<_>>()?;
+
+    Ok((inputs, gates))
+}
+
+/// Parses `a AND b -> c`, with any number of inputs joined by the same operation, as well as
+/// `NOT a -> b` and the constants `0 -> a` and `1 -> a`
+fn parse_gate(line: &Line) -> ParseResult<Gate> {
+    let (operation, output) = line.split_once(" -> ")?;
+    let words: Vec<&str> = operation.text.split_whitespace().collect();
+
+    let (op, inputs) = match words[..] {
+        [] => return Err(line.error("missing gate before '->'")),
+        [value @ ("0" | "1")] => (GateOp::Const(value == "1"), vec![]),
+        ["NOT", input] => (GateOp::Not, vec![input]),
+        ["NOT", ..] => return Err(line.error("NOT takes exactly one input")),
+        [input] => return Err(line.error(format!("'{input}' isn't a gate"))),
+        [first, name, ..] => {
+            let op = GateOp::from_name(name)
+                .ok_or_else(|| line.error(format!("unknown gate operation '{name}'")))?;
+
+            let mut inputs = vec![first];
+            for pair in words[1..].chunks(2) {
+                match *pair {
+                    [name, input] if name == op.name() => inputs.push(input),
+                    [name, _] => {
+                        return Err(line
+                            .error(format!("can't mix {} with '{name}' in one gate", op.name())))
+                    }
+                    [name] => return Err(line.error(format!("missing input after '{name}'"))),
+                    _ => unreachable!("chunks are one or two words"),
+                }
+            }
+            (op, inputs)
+        }
+    };
+
+    Ok(Gate {
+        op,
+        inputs: inputs.into_iter().map(str::to_string).collect(),
+        output: output.text.trim().to_string(),
+    })
+}
+
+/// Evaluates every gate whose inputs are known, returning whether any known output changed
+pub fn step(state: &mut CircuitState, gates: &[Gate]) -> bool {
+    let mut next_state = state.clone();
+    let mut modified = false;
+    for gate in gates {
+        let inputs: Option<Vec<bool>> = gate
+            .inputs
+            .iter()
+            .map(|input| state.get(input).copied())
+            .collect();
+        if let Some(inputs) = inputs {
+            let new_value = gate.op.eval(inputs);
+
+            if let Some(old_value) = state.get(&gate.output) {
+                modified = modified || (new_value != *old_value);
+            }
+            next_state.insert(gate.output.clone(), new_value);
+        }
+    }
+
+    std::mem::swap(state, &mut next_state);
+    modified
+}
+
+/// Reads the number on the wires starting with `var`, `var00` being the lowest bit
+pub fn read_value(var: char, state: &CircuitState) -> u64 {
+    let mut wire_values: Vec<_> = state
+        .keys()
+        .filter(|&key| key.starts_with(var))
+        .cloned()
+        .collect();
+    wire_values.sort();
+
+    let mut output = 0;
+    for zvalue in wire_values.iter().rev() {
+        output = (output << 1) + (*state.get(zvalue).unwrap() as u64);
+    }
+
+    output
+}
+
+/// Sets the first `width` `variable` wires to the bits of `value`. Only wires already in `state`
+/// are changed.
+pub fn set_state(variable: char, value: u64, width: usize, state: &mut CircuitState) {
+    for i in 0..width {
+        let wire = format!("{variable}{i:0>2}");
+        let wire_value = ((value >> i) & 1) == 1;
+
+        if let Some(value) = state.get_mut(&wire) {
+            *value = wire_value;
+        }
+    }
+}
+
+/// Adds `x` and `y` with the circuit
+pub fn run_circuit(x: u64, y: u64, gates: &[Gate]) -> Result<u64, CircuitError> {
+    Ok(CompiledCircuit::new(gates)?.add(x, y))
+}
+
+/// Starting from wire, search backwards through the gates to find all wires which affect the
+/// result
+pub fn search_gates(wire: String, gates: &[Gate], backward: bool) -> HashSet<String> {
+    let mut visited = HashSet::new();
+    let mut frontier = vec![wire];
+    while let Some(wire) = frontier.pop() {
+        if visited.contains(&wire) {
+            continue;
+        } else {
+            visited.insert(wire.clone());
+        }
+
+        for gate in gates {
+            match backward {
+                true if gate.output == wire => {
+                    frontier.extend(gate.inputs.iter().cloned());
+                }
+                false if gate.inputs.contains(&wire) => {
+                    frontier.push(gate.output.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    visited
+}
+
+pub struct CrossedWires;
+
+impl Solution for CrossedWires {
+    fn part1(puzzle_input: String) -> Answer {
+        let circuit: Circuit = puzzle_input.parse().unwrap();
+
+        circuit.output().unwrap().into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let Circuit { gates, .. } = puzzle_input.parse().unwrap();
+
+        let swaps = adder::find_swaps(&gates, 4).expect("four swaps should fix the adder");
+
+        Answer::list(adder::swapped_wires(&swaps))
+    }
+}
+
+/// Circuits the tests of the wires modules share
+#[cfg(test)]
+mod fixtures {
+    use super::{Circuit, Gate, GateOp};
+
+    /// [`ripple_adder`] for two bit numbers, as text to break in different ways
+    pub const TWO_BIT_ADDER: &str = "x00 XOR y00 -> z00
+        x00 AND y00 -> c00
+        x01 XOR y01 -> s01
+        x01 AND y01 -> a01
+        s01 XOR c00 -> z01
+        s01 AND c00 -> p01
+        a01 OR p01 -> z02";
+
+    /// The gates listed in `text`, one per line with any indentation
+    pub fn gates(text: &str) -> Vec<Gate> {
+        let text: Vec<_> = text.lines().map(str::trim).collect();
+        format!("x00: 1\n\n{}", text.join("\n"))
+            .parse::<Circuit>()
+            .unwrap()
+            .gates
+    }
+
+    /// A working adder for `bits` bit numbers, laid out as in the [`adder`](super::adder) docs
+    pub fn ripple_adder(bits: usize) -> Vec<Gate> {
+        let wire = |name: &str, i: usize| format!("{name}{i:02}");
+        let gate =
+            |lhs: String, op, rhs: String, output: String| Gate::binary(&lhs, op, &rhs, &output);
+        let mut gates = vec![
+            gate(wire("x", 0), GateOp::Xor, wire("y", 0), wire("z", 0)),
+            gate(wire("x", 0), GateOp::And, wire("y", 0), wire("c", 0)),
+        ];
+        for i in 1..bits {
+            let carry = if i + 1 == bits {
+                wire("z", bits)
+            } else {
+                wire("c", i)
+            };
+            gates.extend([
+                gate(wire("x", i), GateOp::Xor, wire("y", i), wire("s", i)),
+                gate(wire("x", i), GateOp::And, wire("y", i), wire("a", i)),
+                gate(wire("s", i), GateOp::Xor, wire("c", i - 1), wire("z", i)),
+                gate(wire("s", i), GateOp::And, wire("c", i - 1), wire("p", i)),
+                gate(wire("a", i), GateOp::Or, wire("p", i), carry),
+            ]);
+        }
+
+        gates
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "x00: 1
+x01: 0
+x02: 1
+x03: 1
+x04: 0
+y00: 1
+y01: 1
+y02: 1
+y03: 1
+y04: 1
+
+ntg XOR fgs -> mjb
+y02 OR x01 -> tnw
+kwq OR kpj -> z05
+x00 OR x03 -> fst
+tgd XOR rvg -> z01
+vdt OR tnw -> bfw
+bfw AND frj -> z10
+ffh OR nrd -> bqk
+y00 AND y03 -> djm
+y03 OR y00 -> psh
+bqk OR frj -> z08
+tnw OR fst -> frj
+gnj AND tgd -> z11
+bfw XOR mjb -> z00
+x03 OR x00 -> vdt
+gnj AND wpb -> z02
+x04 AND y00 -> kjc
+djm OR pbm -> qhw
+nrd AND vdt -> hwm
+kjc AND fst -> rvg
+y04 OR y02 -> fgs
+y01 AND x02 -> pbm
+ntg OR kjc -> kwq
+psh XOR fgs -> tgd
+qhw XOR tgd -> z09
+pbm OR djm -> kpj
+x03 XOR y03 -> ffh
+x00 XOR y04 -> ntg
+bfw OR bqk -> z06
+nrd XOR fgs -> wpb
+frj XOR qhw -> z04
+bqk OR frj -> z07
+y03 OR x01 -> nrd
+hwm AND bqk -> z03
+tgd XOR rvg -> z12
+tnw OR pbm -> gnj";
+
+    #[test]
+    fn parses_circuit() {
+        let circuit: Circuit = EXAMPLE.parse().unwrap();
+        assert_eq!(circuit.inputs.len(), 10);
+        assert_eq!(circuit.gates.len(), 36);
+        assert_eq!(
+            circuit.gates[0],
+            Gate::binary("ntg", GateOp::Xor, "fgs", "mjb")
+        );
+
+        let err = "x00: 1\n\nx00 NOPE y00 -> z00"
+            .parse::<Circuit>()
+            .unwrap_err();
+        assert_eq!(err.line, 3);
+    }
+
+    #[test]
+    fn example() {
+        let circuit: Circuit = EXAMPLE.parse().unwrap();
+        assert_eq!(circuit.output(), Ok(2024));
+    }
+
+    #[test]
+    fn parses_general_gates() {
+        let circuit: Circuit = "a: 1
+b: 1
+c: 0
+
+a NAND b NAND c -> z00
+NOT c -> z01
+a XNOR b -> z02
+1 -> one
+one NOR c -> z03
+a XOR b XOR one -> z04"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            circuit.gates[0].inputs,
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+        assert_eq!(circuit.gates[3].op, GateOp::Const(true));
+        assert_eq!(circuit.output(), Ok(0b10111));
+
+        let lines: Vec<_> = circuit.gates.iter().map(Gate::to_string).collect();
+        assert_eq!(lines[0], "a NAND b NAND c -> z00");
+        assert_eq!(lines[1], "NOT c -> z01");
+        assert_eq!(lines[3], "1 -> one");
+    }
+
+    #[test]
+    fn rejects_malformed_gates() {
+        let error = |gate: &str| {
+            format!("a: 1\n\n{gate}")
+                .parse::<Circuit>()
+                .unwrap_err()
+                .message
+        };
+
+        assert_eq!(
+            error("a AND b OR c -> d"),
+            "can't mix AND with 'OR' in one gate"
+        );
+        assert_eq!(error("NOT a b -> d"), "NOT takes exactly one input");
+        assert_eq!(error("a AND -> d"), "missing input after 'AND'");
+        assert_eq!(error("a -> d"), "'a' isn't a gate");
+    }
+
+    #[test]
+    fn rejects_other_wire_values() {
+        let error = "x00: 1\ny00: 7\n\nx00 AND y00 -> z00"
+            .parse::<Circuit>()
+            .unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "'7' isn't a wire value, expected 0 or 1");
+    }
+
+    #[test]
+    fn sets_only_existing_wires() {
+        let mut state = CircuitState::from([("x00".to_string(), false), ("x02".to_string(), true)]);
+        set_state('x', 0b011, 3, &mut state);
+        assert_eq!(state.len(), 2);
+        assert!(state["x00"]);
+        assert!(!state["x02"]);
+    }
+}
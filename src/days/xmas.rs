@@ -1,6 +1,8 @@
-use std::{char, ops::Add, usize};
+//! Day 4: Ceres Search
 
-use crate::solution::Solution;
+use std::{ops::Add, str::FromStr};
+
+use crate::{answer::Answer, parse::ParseError, solution::Solution};
 
 const DIRS: [Position; 8] = [
     Position { x: 0, y: 1 },
@@ -13,92 +15,25 @@ const DIRS: [Position; 8] = [
     Position { x: -1, y: -1 },
 ];
 
-type WordSearch = Vec<Vec<char>>;
-
-#[derive(Clone, Copy)]
-struct Position {
-    x: i64,
-    y: i64,
-}
-
-impl Add for Position {
-    type Output = Position;
-    fn add(self, rhs: Self) -> Self::Output {
-        Position {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
-    }
-}
-
-/// Defines an iterator which handles bounds checking on a search board
-#[derive(Clone)]
-struct SearchIter<'a> {
-    search: &'a WordSearch,
-    position: Position,
-    direction: Position,
-    steps: u32,
-    length: u32,
-}
-
-impl<'a> Iterator for SearchIter<'a> {
-    type Item = char;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.position.y < 0
-            || self.position.y >= self.search.len() as i64
-            || self.position.x < 0
-            || self.position.x >= self.search[0].len() as i64
-            || self.length <= self.steps
-        {
-            return None;
-        }
-
-        let val = Some(self.search[self.position.y as usize][self.position.x as usize]);
-
-        self.position = self.position + self.direction;
-        self.steps += 1;
-
-        val
-    }
-}
-
-pub struct XmasSearchSolution;
-
-impl XmasSearchSolution {
-    fn parse_input(puzzle_input: String) -> WordSearch {
-        return puzzle_input
-            .lines()
-            .map(|l| Vec::from_iter(l.chars()))
-            .collect();
-    }
-
-    fn build_iterators<'a>(search: &'a WordSearch, position: &Position) -> Vec<SearchIter<'a>> {
-        DIRS.map(|direction| SearchIter {
-            search: &search,
-            length: 4,
-            steps: 0,
-            position: *position,
-            direction,
-        })
-        .to_vec()
-    }
+/// The letter grid, indexed `rows[y][x]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordSearch {
+    pub rows: Vec<Vec<char>>,
 }
 
-impl Solution for XmasSearchSolution {
-    fn part1(puzzle_input: String) -> String {
-        let search = XmasSearchSolution::parse_input(puzzle_input);
-
+impl WordSearch {
+    /// Number of times `XMAS` appears in any of the eight directions
+    pub fn count_xmas(&self) -> usize {
         // Build
         let mut iterators: Vec<SearchIter> = Vec::new();
-        for (y, row) in search.iter().enumerate() {
+        for (y, row) in self.rows.iter().enumerate() {
             for (x, char) in row.iter().enumerate() {
                 if *char != 'X' {
                     continue;
                 };
 
                 iterators.append(&mut XmasSearchSolution::build_iterators(
-                    &search,
+                    self,
                     &Position {
                         x: x as i64,
                         y: y as i64,
@@ -111,18 +46,16 @@ impl Solution for XmasSearchSolution {
             .iter_mut()
             .filter_map(|s| s.eq("XMAS".chars()).then_some(1))
             .count()
-            .to_string()
     }
 
-    fn part2(puzzle_input: String) -> String {
-        let search = XmasSearchSolution::parse_input(puzzle_input);
-
+    /// Number of `MAS` crosses, each diagonal may read either way
+    pub fn count_x_mas(&self) -> usize {
         let mut total = 0;
-        for (y, row) in search.iter().enumerate() {
+        for (y, row) in self.rows.iter().enumerate() {
             for (x, _) in row.iter().enumerate() {
                 // Define 2 different diagonal iterators and compare them to MAS & SAM
                 let diag1 = SearchIter {
-                    search: &search,
+                    search: self,
                     position: Position {
                         x: x as i64,
                         y: y as i64,
@@ -133,7 +66,7 @@ impl Solution for XmasSearchSolution {
                 };
 
                 let diag2 = SearchIter {
-                    search: &search,
+                    search: self,
                     position: Position {
                         x: (x + 2) as i64,
                         y: y as i64,
@@ -151,7 +84,94 @@ impl Solution for XmasSearchSolution {
             }
         }
 
-        total.to_string()
+        total
+    }
+}
+
+impl FromStr for WordSearch {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(WordSearch {
+            rows: s.lines().map(|l| Vec::from_iter(l.chars())).collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Add for Position {
+    type Output = Position;
+    fn add(self, rhs: Self) -> Self::Output {
+        Position {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+/// Defines an iterator which handles bounds checking on a search board
+#[derive(Clone)]
+pub struct SearchIter<'a> {
+    pub search: &'a WordSearch,
+    pub position: Position,
+    pub direction: Position,
+    pub steps: u32,
+    pub length: u32,
+}
+
+impl<'a> Iterator for SearchIter<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position.y < 0
+            || self.position.y >= self.search.rows.len() as i64
+            || self.position.x < 0
+            || self.position.x >= self.search.rows[0].len() as i64
+            || self.length <= self.steps
+        {
+            return None;
+        }
+
+        let val = Some(self.search.rows[self.position.y as usize][self.position.x as usize]);
+
+        self.position = self.position + self.direction;
+        self.steps += 1;
+
+        val
+    }
+}
+
+pub struct XmasSearchSolution;
+
+impl XmasSearchSolution {
+    fn build_iterators<'a>(search: &'a WordSearch, position: &Position) -> Vec<SearchIter<'a>> {
+        DIRS.map(|direction| SearchIter {
+            search,
+            length: 4,
+            steps: 0,
+            position: *position,
+            direction,
+        })
+        .to_vec()
+    }
+}
+
+impl Solution for XmasSearchSolution {
+    fn part1(puzzle_input: String) -> Answer {
+        let search: WordSearch = puzzle_input.parse().unwrap();
+
+        search.count_xmas().into()
+    }
+
+    fn part2(puzzle_input: String) -> Answer {
+        let search: WordSearch = puzzle_input.parse().unwrap();
+
+        search.count_x_mas().into()
     }
 }
 
@@ -161,15 +181,14 @@ mod tests {
 
     #[test]
     fn test_iterator() {
-        let search = XmasSearchSolution::parse_input(
-            "abcdef
+        let search: WordSearch = "abcdef
 aBcdef
 abCdef
 abcDef
 abcdef
 abcdef"
-                .to_string(),
-        );
+            .parse()
+            .unwrap();
 
         // Right
         let mut search_iter = SearchIter {
@@ -228,4 +247,23 @@ abcdef"
         assert_eq!(search_iter.next(), Some('a'));
         assert_eq!(search_iter.next(), None);
     }
+
+    #[test]
+    fn counts_words() {
+        let search: WordSearch = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX"
+            .parse()
+            .unwrap();
+
+        assert_eq!(search.count_xmas(), 18);
+        assert_eq!(search.count_x_mas(), 9);
+    }
 }
@@ -1,4 +1,7 @@
-
+pub mod answer;
+pub mod cancel;
 pub mod days;
+pub mod memo;
+pub mod parse;
 pub mod solution;
 pub mod utils;
@@ -1,9 +1,17 @@
-use std::path::Path;
-use std::{env, fs};
 use std::io;
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use std::{env, fs, panic, thread};
+
+use itertools::Itertools;
 
+use advent_of_code_2024::answer::Answer;
+use advent_of_code_2024::cancel::{CancelToken, Cancelled};
 use advent_of_code_2024::days::bridge_repair::BridgeSolution;
-use advent_of_code_2024::days::chronospatial_comp::ChronospatialSolution;
+use advent_of_code_2024::days::chronospatial_comp::{
+    disasm, ChronospatialSolution, Computer, Machine, State, VmError, STEP_LIMIT,
+};
 use advent_of_code_2024::days::claw::ClawContraption;
 use advent_of_code_2024::days::day1::ListSimilarity;
 use advent_of_code_2024::days::disk::DiskFragmenter;
@@ -24,8 +32,12 @@ use advent_of_code_2024::days::resonant::Resonant;
 use advent_of_code_2024::days::restroom::RestroomSolution;
 use advent_of_code_2024::days::towel::TowelSolution;
 use advent_of_code_2024::days::warehouse::WarehouseSolution;
-use advent_of_code_2024::days::wires::CrossedWires;
+use advent_of_code_2024::days::wires::{
+    adder, bdd::SymbolicCircuit, compiled::CompiledCircuit, equivalence::EquivalenceTest, export,
+    faults::FaultReport, Circuit, CrossedWires,
+};
 use advent_of_code_2024::days::xmas::XmasSearchSolution;
+use advent_of_code_2024::parse;
 use advent_of_code_2024::solution::Solution;
 
 fn get_test_input(day: u64) -> io::Result<String> {
@@ -38,54 +50,267 @@ fn get_input(day: u64) -> io::Result<String> {
     fs::read_to_string(Path::new(&s))
 }
 
-fn run_solutions<T: Solution>(test_input: String, input: String) {
+/// Known answers for a day, one per line, used to check refactors haven't broken anything
+fn get_expected_answers(day: u64) -> Vec<String> {
+    let s = format!("inputs/day{day}/answers.txt");
+    fs::read_to_string(Path::new(&s))
+        .map(|answers| answers.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+struct RunOptions {
+    expected: Vec<String>,
+    /// Seconds each part gets before we give up on it
+    timeout: Option<f64>,
+}
+
+type Part = fn(String, &CancelToken) -> Result<Answer, Cancelled>;
+
+const USAGE: &str = "usage: advent-of-code-2024 <day> [--timeout <seconds>] | advent-of-code-2024 disasm|trace|circuit ...
+
+  --timeout <seconds>  give up on a part after this long. Parts which never check for being
+                       cancelled can't be stopped and keep running in the background, which
+                       slows down the parts after them.";
+
+/// How long a part gets to notice it's been cancelled before we stop waiting for it
+const CANCEL_GRACE: Duration = Duration::from_millis(500);
+
+/// Runs a part on its own thread so we can stop waiting once the timeout is up, even when the
+/// part never checks its cancel token
+fn run_part(part: Part, input: String, timeout: Option<f64>) -> Result<Answer, Cancelled> {
+    let Some(timeout) = timeout.map(Duration::from_secs_f64) else {
+        return part(input, &CancelToken::new());
+    };
+
+    let cancel = CancelToken::with_timeout(timeout);
+    let worker_cancel = cancel.clone();
+    let (sender, receiver) = mpsc::channel();
+    let worker = thread::spawn(move || {
+        let _ = sender.send(part(input, &worker_cancel));
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => {
+            let _ = worker.join();
+            result
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            cancel.cancel();
+            // Parts which check their token stop straight away, so they're done before the next
+            // part starts timing. One which finishes in the meantime still gets its answer.
+            match receiver.recv_timeout(CANCEL_GRACE) {
+                Ok(result) => {
+                    let _ = worker.join();
+                    result
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    eprintln!(
+                        "part ignored being cancelled and is still running in the background"
+                    );
+                    Err(Cancelled)
+                }
+                Err(RecvTimeoutError::Disconnected) => resume_panic(worker),
+            }
+        }
+        Err(RecvTimeoutError::Disconnected) => resume_panic(worker),
+    }
+}
+
+/// Passes on the panic of a part which stopped without sending its answer
+fn resume_panic(worker: thread::JoinHandle<()>) -> ! {
+    match worker.join() {
+        Err(payload) => panic::resume_unwind(payload),
+        Ok(()) => unreachable!("parts always send their answer"),
+    }
+}
+
+fn print_answer(
+    result: Result<Answer, Cancelled>,
+    expected: Option<&String>,
+    options: &RunOptions,
+) {
+    let answer = match result {
+        Ok(answer) => answer,
+        Err(Cancelled) => {
+            println!("timed out after {} s", options.timeout.unwrap_or_default());
+            return;
+        }
+    };
+
+    println!("{}", answer.report());
+
+    match expected {
+        Some(expected) if answer.matches(expected) => println!("(correct)"),
+        Some(expected) => println!("(wrong, expected {})", expected.trim()),
+        None => {}
+    }
+}
+
+fn run_solutions<T: Solution>(test_input: String, input: String, options: &RunOptions) {
     println!("Tests");
-    let test_answer1 = T::part1(test_input.clone());
-    println!("{test_answer1}");
+    let test_answer1 = run_part(T::part1_cancellable, test_input.clone(), options.timeout);
+    print_answer(test_answer1, None, options);
 
-    //let test_answer2 = T::part2(test_input);
-    //println!("{test_answer2}");
+    //let test_answer2 = run_part(T::part2_cancellable, test_input, options.timeout);
+    //print_answer(test_answer2, None, options);
 
     println!("Solutions");
-    let answer1 = T::part1(input.clone());
-    println!("{answer1}");
+    let answer1 = run_part(T::part1_cancellable, input.clone(), options.timeout);
+    print_answer(answer1, options.expected.first(), options);
+
+    let answer2 = run_part(T::part2_cancellable, input, options.timeout);
+    print_answer(answer2, options.expected.get(1), options);
+}
+
+/// Reads a day 17 program from `path`, either a whole puzzle input or just the comma separated
+/// program (which then starts with all registers at 0). Defaults to the day 17 input.
+fn read_computer(path: Option<String>) -> Computer {
+    let path = path.unwrap_or_else(|| "inputs/day17/input.txt".to_string());
+    let text = fs::read_to_string(Path::new(&path)).expect("File is read correctly");
+
+    text.parse::<Computer>().unwrap_or_else(|_| Computer {
+        state: State { a: 0, b: 0, c: 0 },
+        program: parse::ints(&text).unwrap_or_else(|err| panic!("{path}: {err}")),
+    })
+}
+
+/// `disasm [file]` prints the disassembly of a day 17 program
+fn disassemble_command(mut args: impl Iterator<Item = String>) {
+    let computer = read_computer(args.next());
+    print!("{}", disasm::disassemble(&computer.program));
+}
+
+/// `trace [file]` runs a day 17 program printing every step
+fn trace_command(mut args: impl Iterator<Item = String>) {
+    let computer = read_computer(args.next());
+    let mut machine = Machine::new(&computer.program, computer.state);
+
+    while machine.steps < STEP_LIMIT {
+        match machine.step() {
+            Ok(Some(step)) => println!("{step}"),
+            Ok(None) => break,
+            Err(err) => {
+                println!("error: {err}");
+                break;
+            }
+        }
+    }
+    if machine.steps >= STEP_LIMIT && !machine.is_halted() {
+        println!(
+            "{}",
+            VmError::StepLimit {
+                steps: machine.steps
+            }
+        );
+    }
+
+    println!("output: {}", machine.output.iter().join(","));
+}
+
+/// `circuit dot|verilog [file]` writes a day 24 circuit out for Graphviz or a Verilog simulator.
+/// The DOT output highlights the wires which don't fit the adder pattern. `circuit check [file]`
+/// tests the circuit against addition instead, and `circuit prove [file]` checks every output's
+/// function symbolically. `circuit faults [file]` ranks the gates behind any wrong bits.
+fn circuit_command(mut args: impl Iterator<Item = String>) {
+    let format = args.next();
+    let path = args
+        .next()
+        .unwrap_or_else(|| "inputs/day24/input.txt".to_string());
+    let text = fs::read_to_string(Path::new(&path)).expect("File is read correctly");
+    let circuit: Circuit = text.parse().unwrap_or_else(|err| panic!("{path}: {err}"));
 
-    let answer2 = T::part2(input);
-    println!("{answer2}");
+    match format.as_deref() {
+        Some("dot") => {
+            let suspects = adder::check_structure(&circuit.gates)
+                .into_iter()
+                .map(|miswired| miswired.wire)
+                .collect();
+            print!("{}", export::to_dot(&circuit.gates, &suspects));
+        }
+        Some("verilog") => print!("{}", export::to_verilog(&circuit.gates, "circuit")),
+        Some("check") => match CompiledCircuit::new(&circuit.gates) {
+            Ok(compiled) => print!("{}", EquivalenceTest::new().check_addition(&compiled)),
+            Err(err) => println!("{err}"),
+        },
+        Some("prove") => match SymbolicCircuit::new(&circuit.gates) {
+            Ok(mut symbolic) => match symbolic.check_addition() {
+                Some(mismatch) => println!("{mismatch}"),
+                None => println!("the circuit adds its inputs"),
+            },
+            Err(err) => println!("{err}"),
+        },
+        Some("faults") => match FaultReport::new(&circuit.gates) {
+            Ok(report) => print!("{report}"),
+            Err(err) => println!("{err}"),
+        },
+        _ => println!("usage: advent-of-code-2024 circuit dot|verilog|check|prove|faults [file]"),
+    }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let day: u64 = args[1].parse().unwrap();
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("disasm") {
+        args.next();
+        return disassemble_command(args);
+    }
+    if args.peek().map(String::as_str) == Some("trace") {
+        args.next();
+        return trace_command(args);
+    }
+    if args.peek().map(String::as_str) == Some("circuit") {
+        args.next();
+        return circuit_command(args);
+    }
+
+    let mut day = None;
+    let mut timeout = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return;
+            }
+            "--timeout" => {
+                let seconds = args.next().and_then(|s| s.parse::<f64>().ok());
+                timeout = Some(seconds.expect("--timeout takes a number of seconds"));
+            }
+            _ => day = Some(arg.parse::<u64>().expect("day should be a number")),
+        }
+    }
+    let day = day.expect(USAGE);
 
     let test_input = get_test_input(day).expect("File is read correctly");
     let input = get_input(day).expect("File is read correctly");
+    let options = RunOptions {
+        expected: get_expected_answers(day),
+        timeout,
+    };
 
     match day {
-        1 => run_solutions::<ListSimilarity>(test_input, input),
-        2 => run_solutions::<MonotonicReport>(test_input, input),
-        3 => run_solutions::<MullSolution>(test_input, input),
-        4 => run_solutions::<XmasSearchSolution>(test_input, input),
-        5 => run_solutions::<PrintOrder>(test_input, input),
-        6 => run_solutions::<GuardSolution>(test_input, input),
-        7 => run_solutions::<BridgeSolution>(test_input, input),
-        8 => run_solutions::<Resonant>(test_input, input),
-        9 => run_solutions::<DiskFragmenter>(test_input, input),
-        10 => run_solutions::<HoofItSolution>(test_input, input),
-        11 => run_solutions::<PebbleCounterSolution>(test_input, input),
-        12 => run_solutions::<GardenGroupSolution>(test_input, input),
-        13 => run_solutions::<ClawContraption>(test_input, input),
-        14 => run_solutions::<RestroomSolution>(test_input, input),
-        15 => run_solutions::<WarehouseSolution>(test_input, input),
-        16 => run_solutions::<ReindeerSolution>(test_input, input),
-        17 => run_solutions::<ChronospatialSolution>(test_input, input),
-        18 => run_solutions::<RamRunSolution>(test_input, input),
-        19 => run_solutions::<TowelSolution>(test_input, input),
-        20 => run_solutions::<RaceCondition>(test_input, input),
-        21 => run_solutions::<Keypad>(test_input, input),
-        22 => run_solutions::<MonkeyMarket>(test_input, input),
-        23 => run_solutions::<LanParty>(test_input, input),
-        24 => run_solutions::<CrossedWires>(test_input, input),
-        _ => println!("No day solution for day {day}")
+        1 => run_solutions::<ListSimilarity>(test_input, input, &options),
+        2 => run_solutions::<MonotonicReport>(test_input, input, &options),
+        3 => run_solutions::<MullSolution>(test_input, input, &options),
+        4 => run_solutions::<XmasSearchSolution>(test_input, input, &options),
+        5 => run_solutions::<PrintOrder>(test_input, input, &options),
+        6 => run_solutions::<GuardSolution>(test_input, input, &options),
+        7 => run_solutions::<BridgeSolution>(test_input, input, &options),
+        8 => run_solutions::<Resonant>(test_input, input, &options),
+        9 => run_solutions::<DiskFragmenter>(test_input, input, &options),
+        10 => run_solutions::<HoofItSolution>(test_input, input, &options),
+        11 => run_solutions::<PebbleCounterSolution>(test_input, input, &options),
+        12 => run_solutions::<GardenGroupSolution>(test_input, input, &options),
+        13 => run_solutions::<ClawContraption>(test_input, input, &options),
+        14 => run_solutions::<RestroomSolution>(test_input, input, &options),
+        15 => run_solutions::<WarehouseSolution>(test_input, input, &options),
+        16 => run_solutions::<ReindeerSolution>(test_input, input, &options),
+        17 => run_solutions::<ChronospatialSolution>(test_input, input, &options),
+        18 => run_solutions::<RamRunSolution>(test_input, input, &options),
+        19 => run_solutions::<TowelSolution>(test_input, input, &options),
+        20 => run_solutions::<RaceCondition>(test_input, input, &options),
+        21 => run_solutions::<Keypad>(test_input, input, &options),
+        22 => run_solutions::<MonkeyMarket>(test_input, input, &options),
+        23 => run_solutions::<LanParty>(test_input, input, &options),
+        24 => run_solutions::<CrossedWires>(test_input, input, &options),
+        _ => println!("No day solution for day {day}"),
     }
 }
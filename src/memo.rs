@@ -0,0 +1,226 @@
+//! Scoped memoization for recursive solutions.
+//!
+//! A [`Memo`] is an ordinary value owned by the caller, so each call (or test) gets a fresh cache
+//! and nothing leaks between runs. It keeps hit/miss counts so we can see whether the caching is
+//! actually pulling its weight.
+
+use std::{borrow::Borrow, collections::HashMap, fmt, hash::Hash};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Number of entries currently stored
+    pub size: usize,
+    /// Results that were computed but not stored because the memo was full
+    pub rejected: u64,
+    pub capacity: Option<usize>,
+}
+
+impl MemoStats {
+    /// Fraction of lookups answered from the cache, 0 if nothing was looked up yet
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+
+    /// The stats of two memos taken together, e.g. when each input gets a fresh memo
+    pub fn merge(self, other: MemoStats) -> MemoStats {
+        MemoStats {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+            size: self.size + other.size,
+            rejected: self.rejected + other.rejected,
+            capacity: self.capacity.zip(other.capacity).map(|(a, b)| a + b),
+        }
+    }
+}
+
+impl fmt::Display for MemoStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate), {} entries",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0,
+            self.size
+        )?;
+        if let Some(capacity) = self.capacity {
+            write!(f, " of {capacity}, {} rejected", self.rejected)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    table: HashMap<K, V>,
+    capacity: Option<usize>,
+    hits: u64,
+    misses: u64,
+    rejected: u64,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Memo<K, V> {
+        Memo {
+            table: HashMap::new(),
+            capacity: None,
+            hits: 0,
+            misses: 0,
+            rejected: 0,
+        }
+    }
+
+    /// A memo which stops storing new results once it holds `capacity` entries
+    pub fn bounded(capacity: usize) -> Memo<K, V> {
+        Memo {
+            capacity: Some(capacity),
+            ..Memo::new()
+        }
+    }
+
+    /// Looks up a stored result, counting the hit or miss
+    pub fn get<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        match self.table.get(key) {
+            Some(value) => {
+                self.hits += 1;
+                Some(value.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Stores a result unless the memo is already full
+    pub fn insert(&mut self, key: K, value: V) {
+        let full = self
+            .capacity
+            .is_some_and(|capacity| self.table.len() >= capacity);
+
+        if full && !self.table.contains_key(&key) {
+            self.rejected += 1;
+        } else {
+            self.table.insert(key, value);
+        }
+    }
+
+    /// Returns the stored result for `key`, or runs `compute` and stores what it returns.
+    /// `compute` is handed the memo back so recursive calls can share it.
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+
+        let value = compute(self);
+        self.insert(key, value.clone());
+        value
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+            size: self.table.len(),
+            rejected: self.rejected,
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        if n < 2 {
+            return n;
+        }
+
+        memo.get_or_compute(n, |memo| fib(n - 1, memo) + fib(n - 2, memo))
+    }
+
+    #[test]
+    fn memoizes_recursive_calls() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(90, &mut memo), 2880067194370816120);
+
+        let stats = memo.stats();
+        assert_eq!(stats.size, 89);
+        assert_eq!(stats.misses, 89);
+        assert_eq!(stats.hits, 87);
+    }
+
+    #[test]
+    fn caches_are_independent() {
+        let mut first = Memo::new();
+        fib(20, &mut first);
+
+        let second: Memo<u64, u64> = Memo::new();
+        assert!(second.is_empty());
+        assert_eq!(second.stats(), MemoStats::default());
+    }
+
+    #[test]
+    fn bounded_memo_stops_growing() {
+        let mut memo = Memo::bounded(10);
+        assert_eq!(fib(30, &mut memo), 832040);
+
+        let stats = memo.stats();
+        assert_eq!(stats.size, 10);
+        assert!(stats.rejected > 0);
+        assert_eq!(stats.capacity, Some(10));
+    }
+
+    #[test]
+    fn borrowed_lookups() {
+        let mut memo: Memo<String, usize> = Memo::new();
+        memo.insert("abc".to_string(), 3);
+
+        assert_eq!(memo.get("abc"), Some(3));
+        assert_eq!(memo.get("abd"), None);
+        assert_eq!(memo.stats().hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn merges_stats() {
+        let mut first = Memo::new();
+        fib(10, &mut first);
+        let mut second = Memo::new();
+        fib(20, &mut second);
+
+        let stats = first.stats().merge(second.stats());
+        assert_eq!(stats.size, 9 + 19);
+        assert_eq!(stats.hits, 7 + 17);
+        assert_eq!(stats.capacity, None);
+        assert_eq!(
+            stats.to_string(),
+            "24 hits, 28 misses (46.2% hit rate), 28 entries"
+        );
+    }
+}
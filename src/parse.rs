@@ -0,0 +1,472 @@
+//! Helpers for pulling structure out of puzzle inputs.
+//!
+//! Inputs are viewed as [`Section`]s (runs of lines separated by blank lines) made of [`Line`]s.
+//! Both remember where they came from, so every helper that can fail reports the 1-based line
+//! number of the offending input line rather than panicking somewhere inside a day module.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number in the original input
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line,
+            message: message.into(),
+        }
+    }
+
+    /// Shifts an error from parsing a sub-slice of the input so it points into the whole input
+    pub fn offset(self, first_line: usize) -> ParseError {
+        ParseError {
+            line: self.line + first_line - 1,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// A single line of input along with its line number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    pub text: &'a str,
+    pub number: usize,
+}
+
+impl<'a> Line<'a> {
+    /// Wraps a standalone line, e.g. the input to a `FromStr` impl
+    pub fn new(text: &'a str) -> Line<'a> {
+        Line { text, number: 1 }
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.number, message)
+    }
+
+    /// Parses the whole (trimmed) line as a `T`
+    pub fn parse<T>(&self) -> ParseResult<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let text = self.text.trim();
+        text.parse()
+            .map_err(|e| self.error(format!("invalid value '{text}': {e}")))
+    }
+
+    /// Parses the line with a `FromStr` impl that reports [`ParseError`]s itself
+    pub fn parse_item<T>(&self) -> ParseResult<T>
+    where
+        T: FromStr<Err = ParseError>,
+    {
+        self.text
+            .parse()
+            .map_err(|e: ParseError| e.offset(self.number))
+    }
+
+    /// Extracts every integer in the line, see [`ints`]
+    pub fn ints<T>(&self) -> ParseResult<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        int_tokens(self.text)
+            .into_iter()
+            .map(|token| {
+                token
+                    .parse()
+                    .map_err(|e| self.error(format!("invalid integer '{token}': {e}")))
+            })
+            .collect()
+    }
+
+    /// Extracts exactly `N` integers from the line
+    pub fn ints_n<T, const N: usize>(&self) -> ParseResult<[T; N]>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let nums = self.ints()?;
+        let found = nums.len();
+        nums.try_into()
+            .map_err(|_| self.error(format!("expected {N} integers, found {found}")))
+    }
+
+    /// Splits the line around the first `delimiter`, keeping the line number on both halves
+    pub fn split_once(&self, delimiter: &str) -> ParseResult<(Line<'a>, Line<'a>)> {
+        let (lhs, rhs) = self
+            .text
+            .split_once(delimiter)
+            .ok_or_else(|| self.error(format!("expected '{delimiter}' in '{}'", self.text)))?;
+
+        Ok((
+            Line {
+                text: lhs,
+                number: self.number,
+            },
+            Line {
+                text: rhs,
+                number: self.number,
+            },
+        ))
+    }
+
+    /// Splits the line on `delimiter` into exactly `N` parts
+    pub fn split_n<const N: usize>(&self, delimiter: &str) -> ParseResult<[&'a str; N]> {
+        let parts: Vec<_> = self.text.split(delimiter).collect();
+        let found = parts.len();
+        parts.try_into().map_err(|_| {
+            self.error(format!(
+                "expected {N} parts separated by '{delimiter}', found {found}"
+            ))
+        })
+    }
+}
+
+/// A `key: value` pair, the value keeps its line number for further parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field<'a> {
+    pub key: &'a str,
+    pub value: Line<'a>,
+}
+
+/// A run of lines from the input, usually one of the blank-line-separated blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    pub text: &'a str,
+    /// 1-based line number of the first line of the section
+    pub first_line: usize,
+}
+
+impl<'a> Section<'a> {
+    /// Wraps a whole puzzle input
+    pub fn new(text: &'a str) -> Section<'a> {
+        Section {
+            text,
+            first_line: 1,
+        }
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.first_line, message)
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Line<'a>> {
+        let first_line = self.first_line;
+        self.text.lines().enumerate().map(move |(i, text)| Line {
+            text,
+            number: first_line + i,
+        })
+    }
+
+    /// Parses the section with a `FromStr` impl that reports [`ParseError`]s itself
+    pub fn parse_item<T>(&self) -> ParseResult<T>
+    where
+        T: FromStr<Err = ParseError>,
+    {
+        self.text
+            .parse()
+            .map_err(|e: ParseError| e.offset(self.first_line))
+    }
+
+    /// Extracts every integer in the section, see [`ints`]
+    pub fn ints<T>(&self) -> ParseResult<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let mut nums = Vec::new();
+        for line in self.lines() {
+            nums.extend(line.ints()?);
+        }
+        Ok(nums)
+    }
+
+    /// Extracts exactly `N` integers from the section
+    pub fn ints_n<T, const N: usize>(&self) -> ParseResult<[T; N]>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let nums = self.ints()?;
+        let found = nums.len();
+        nums.try_into()
+            .map_err(|_| self.error(format!("expected {N} integers, found {found}")))
+    }
+
+    /// Parses every line as a `key: value` pair, both sides are trimmed
+    pub fn fields(&self) -> ParseResult<Vec<Field<'a>>> {
+        self.lines()
+            .map(|line| {
+                let (key, value) = line.text.split_once(':').ok_or_else(|| {
+                    line.error(format!("expected 'key: value', found '{}'", line.text))
+                })?;
+
+                Ok(Field {
+                    key: key.trim(),
+                    value: Line {
+                        text: value.trim(),
+                        number: line.number,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Looks up the value of a `key: value` line
+    pub fn field(&self, key: &str) -> ParseResult<Line<'a>> {
+        self.fields()?
+            .into_iter()
+            .find(|field| field.key == key)
+            .map(|field| field.value)
+            .ok_or_else(|| self.error(format!("missing field '{key}'")))
+    }
+
+    /// Parses a rectangular character grid, `cell` maps each character to a tile or rejects it
+    /// with `None`. Rows are indexed `grid[y][x]`.
+    pub fn grid<T>(&self, mut cell: impl FnMut(char) -> Option<T>) -> ParseResult<Vec<Vec<T>>> {
+        let mut grid: Vec<Vec<T>> = Vec::new();
+        for line in self.lines() {
+            let row = line
+                .text
+                .chars()
+                .enumerate()
+                .map(|(x, c)| {
+                    cell(c).ok_or_else(|| {
+                        line.error(format!("unexpected character '{c}' in column {}", x + 1))
+                    })
+                })
+                .collect::<ParseResult<Vec<_>>>()?;
+
+            if let Some(first) = grid.first() {
+                if first.len() != row.len() {
+                    return Err(line.error(format!(
+                        "row has width {}, expected {}",
+                        row.len(),
+                        first.len()
+                    )));
+                }
+            }
+            grid.push(row);
+        }
+
+        Ok(grid)
+    }
+}
+
+/// Splits the input into blocks separated by one or more blank lines
+pub fn sections(input: &str) -> Vec<Section<'_>> {
+    let mut sections = Vec::new();
+    // (byte offset, first line) of the section being built, and the byte offset of its end
+    let mut current: Option<(usize, usize)> = None;
+    let mut end = 0;
+    let mut offset = 0;
+
+    for (i, chunk) in input.split_inclusive('\n').enumerate() {
+        let content = chunk.trim_end_matches(['\n', '\r']);
+        if content.trim().is_empty() {
+            if let Some((start, first_line)) = current.take() {
+                sections.push(Section {
+                    text: &input[start..end],
+                    first_line,
+                });
+            }
+        } else {
+            current.get_or_insert((offset, i + 1));
+            end = offset + content.len();
+        }
+        offset += chunk.len();
+    }
+
+    if let Some((start, first_line)) = current {
+        sections.push(Section {
+            text: &input[start..end],
+            first_line,
+        });
+    }
+
+    sections
+}
+
+/// Splits the input into exactly `N` blank-line-separated blocks
+pub fn sections_n<const N: usize>(input: &str) -> ParseResult<[Section<'_>; N]> {
+    let sections = sections(input);
+    let found = sections.len();
+    let line = sections
+        .get(N)
+        .map_or(input.lines().count().max(1), |extra| extra.first_line);
+
+    sections
+        .try_into()
+        .map_err(|_| ParseError::new(line, format!("expected {N} sections, found {found}")))
+}
+
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    Section::new(input).lines()
+}
+
+/// Parses every line of the input as a `T`
+pub fn parse_lines<T>(input: &str) -> ParseResult<Vec<T>>
+where
+    T: FromStr<Err = ParseError>,
+{
+    lines(input).map(|line| line.parse_item()).collect()
+}
+
+/// Extracts every integer in `text`, in order. A `-` directly before a number makes it negative
+/// unless it follows another digit, so `p=4,-3` gives `[4, -3]` and `1-3` gives `[1, 3]`.
+pub fn ints<T>(text: &str) -> ParseResult<Vec<T>>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    Section::new(text).ints()
+}
+
+fn int_tokens(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+            && (i == 0 || !bytes[i - 1].is_ascii_digit());
+
+        if negative || bytes[i].is_ascii_digit() {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            tokens.push(&text[start..i]);
+        } else {
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extracts_signed_ints() {
+        assert_eq!(ints::<i64>("p=4,72 v=24,-91"), Ok(vec![4, 72, 24, -91]));
+        assert_eq!(ints::<i64>("Button A: X+94, Y+34"), Ok(vec![94, 34]));
+        assert_eq!(ints::<i64>("1-3 a: -"), Ok(vec![1, 3]));
+        assert_eq!(ints::<i64>("no numbers"), Ok(vec![]));
+    }
+
+    #[test]
+    fn int_errors_report_line() {
+        let err = ints::<u8>("1,2\n3,300").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        let err = ints::<u32>("\n\n4,-5").unwrap_err();
+        assert_eq!(err.line, 3);
+    }
+
+    #[test]
+    fn exact_int_counts() {
+        let line = lines("p=0,4 v=3,-3").next().unwrap();
+        assert_eq!(line.ints_n::<i32, 4>(), Ok([0, 4, 3, -3]));
+        assert!(line.ints_n::<i32, 3>().is_err());
+    }
+
+    #[test]
+    fn splits_sections() {
+        let input = "a\nb\n\n\nc\n\nd\ne\n";
+        let sections = sections(input);
+
+        assert_eq!(
+            sections,
+            vec![
+                Section {
+                    text: "a\nb",
+                    first_line: 1
+                },
+                Section {
+                    text: "c",
+                    first_line: 5
+                },
+                Section {
+                    text: "d\ne",
+                    first_line: 7
+                },
+            ]
+        );
+        assert_eq!(sections[2].lines().last().unwrap().number, 8);
+    }
+
+    #[test]
+    fn section_counts() {
+        assert!(sections_n::<2>("a\n\nb\n").is_ok());
+        assert_eq!(sections_n::<2>("a\nb\n").unwrap_err().line, 2);
+        assert_eq!(sections_n::<2>("a\n\nb\n\nc").unwrap_err().line, 5);
+    }
+
+    #[test]
+    fn key_value_fields() {
+        let [registers, _] = sections_n("Register A: 729\nRegister B: 0\n\nProgram: 0,1").unwrap();
+
+        assert_eq!(
+            registers.field("Register A").unwrap().parse::<u64>(),
+            Ok(729)
+        );
+        assert_eq!(registers.field("Register B").unwrap().number, 2);
+        assert_eq!(registers.field("Register C").unwrap_err().line, 1);
+        assert_eq!(Section::new("a: 1\nb 2").fields().unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn nested_errors_point_into_input() {
+        #[derive(Debug)]
+        struct Pair;
+
+        impl FromStr for Pair {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Section::new(s).ints_n::<u8, 2>().map(|_| Pair)
+            }
+        }
+
+        assert!(parse_lines::<Pair>("1 2\n3 4").is_ok());
+        assert_eq!(parse_lines::<Pair>("1 2\n3 4\n5").unwrap_err().line, 3);
+
+        let [_, second] = sections_n("1 2\n\n3\n4 5").unwrap();
+        assert_eq!(second.parse_item::<Pair>().unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn parses_grids() {
+        let grid = Section::new("#.\n.#").grid(|c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        });
+        assert_eq!(grid, Ok(vec![vec![true, false], vec![false, true]]));
+
+        let bad_char = Section::new("..\n.x").grid(|c| (c == '.').then_some(()));
+        assert_eq!(bad_char.unwrap_err().line, 2);
+
+        let ragged = Section::new("..\n.\n..").grid(Some);
+        assert_eq!(ragged.unwrap_err().line, 2);
+    }
+}
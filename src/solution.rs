@@ -1,6 +1,22 @@
-
+use crate::{
+    answer::Answer,
+    cancel::{CancelToken, Cancelled},
+};
 
 pub trait Solution {
-    fn part1(puzzle_input: String) -> String;
-    fn part2(puzzle_input: String) -> String;
+    fn part1(puzzle_input: String) -> Answer;
+    fn part2(puzzle_input: String) -> Answer;
+
+    /// [`Solution::part1`] which gives up once `cancel` fires. Only solutions with a slow search
+    /// need to override this.
+    fn part1_cancellable(puzzle_input: String, cancel: &CancelToken) -> Result<Answer, Cancelled> {
+        cancel.check()?;
+        Ok(Self::part1(puzzle_input))
+    }
+
+    /// [`Solution::part2`] which gives up once `cancel` fires
+    fn part2_cancellable(puzzle_input: String, cancel: &CancelToken) -> Result<Answer, Cancelled> {
+        cancel.check()?;
+        Ok(Self::part2(puzzle_input))
+    }
 }